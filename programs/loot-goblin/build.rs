@@ -0,0 +1,134 @@
+//! Compiles `data/event_catalog.json` into `$OUT_DIR/event_catalog.rs`, which
+//! `state/event_catalog.rs` includes as `EVENT_CATALOG` and the `side_effect` ids.
+
+use std::collections::HashSet;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+const CATALOG_PATH: &str = "data/event_catalog.json";
+
+fn main() {
+    println!("cargo:rerun-if-changed={CATALOG_PATH}");
+    let json = fs::read_to_string(CATALOG_PATH).expect("couldn't read the event catalog");
+    let catalog: Value = serde_json::from_str(&json).expect("event catalog isn't valid JSON");
+    let out = generate(&catalog).unwrap_or_else(|e| panic!("invalid event catalog: {e}"));
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("event_catalog.rs");
+    fs::write(out_path, out).expect("couldn't write the compiled event catalog");
+}
+
+fn generate(catalog: &Value) -> Result<String, String> {
+    // Side-effects are referred to by name, their id is their position in the list
+    let side_effects: Vec<&str> = catalog["side_effects"]
+        .as_array()
+        .ok_or("missing side_effects")?
+        .iter()
+        .map(|name| name.as_str().ok_or("side effect names must be strings"))
+        .collect::<Result<_, _>>()?;
+    if side_effects.len() > u8::MAX as usize {
+        return Err(format!(
+            "expected at most 255 side effects, found {}",
+            side_effects.len()
+        ));
+    }
+    let mut out = String::new();
+    // The program matches on these, so reordering the list can't silently swap effects
+    writeln!(out, "pub mod side_effect {{").unwrap();
+    for (i, name) in side_effects.iter().enumerate() {
+        writeln!(out, "    pub const {}: u8 = {i};", constant_name(name)?).unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+    let events = catalog["events"].as_array().ok_or("missing events")?;
+    if events.is_empty() || events.len() > u8::MAX as usize {
+        return Err(format!("expected 1 to 255 events, found {}", events.len()));
+    }
+    let mut ids = HashSet::new();
+    writeln!(
+        out,
+        "pub const EVENT_CATALOG: [EventDef; {}] = [",
+        events.len()
+    )
+    .unwrap();
+    for event in events {
+        let id = event["id"].as_u64().ok_or("event ids must be numbers")?;
+        if id == 0 || id > u8::MAX as u64 || !ids.insert(id) {
+            return Err(format!("event id {id} must be unique and from 1 to 255"));
+        }
+        let text = event["text"]
+            .as_str()
+            .ok_or(format!("event {id} has no text"))?;
+        let choices = event["choices"]
+            .as_array()
+            .filter(|choices| choices.len() == 2)
+            .ok_or(format!("event {id} must have 2 choices"))?;
+        writeln!(out, "    EventDef {{").unwrap();
+        writeln!(out, "        id: {id},").unwrap();
+        writeln!(out, "        text: {text:?},").unwrap();
+        writeln!(out, "        choices: [").unwrap();
+        for choice in choices {
+            let label = choice["label"]
+                .as_str()
+                .ok_or(format!("event {id} has a choice without a label"))?;
+            let pool = choice["side_effects"]
+                .as_array()
+                .filter(|pool| !pool.is_empty())
+                .ok_or(format!("choice {label} needs a side effect pool"))?
+                .iter()
+                .map(|name| {
+                    name.as_str()
+                        .and_then(|name| side_effects.iter().position(|s| *s == name))
+                        .map(|i| i.to_string())
+                        .ok_or(format!("choice {label} has an unknown side effect {name}"))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            writeln!(
+                out,
+                "            EventChoice {{ label: {label:?}, side_effects: &[{}] }},",
+                pool.join(", ")
+            )
+            .unwrap();
+        }
+        writeln!(out, "        ],").unwrap();
+        // Outcome names are checked against `EventOutcome` when the program compiles
+        let weights = event["weights"]
+            .as_object()
+            .ok_or(format!("event {id} has no weights"))?;
+        let mut modifiers = Vec::new();
+        for (outcome, modifier) in weights {
+            let modifier = modifier
+                .as_i64()
+                .filter(|m| (i8::MIN as i64..=i8::MAX as i64).contains(m))
+                .ok_or(format!("event {id} has an invalid {outcome} weight"))?;
+            modifiers.push(format!("(EventOutcome::{outcome}, {modifier})"));
+        }
+        writeln!(
+            out,
+            "        weight_modifiers: &[{}],",
+            modifiers.join(", ")
+        )
+        .unwrap();
+        writeln!(out, "    }},").unwrap();
+    }
+    writeln!(out, "];").unwrap();
+    Ok(out)
+}
+
+/// Name of the constant for a side effect, e.g. `GREEDY_FRENZY` for `GreedyFrenzy`.
+fn constant_name(name: &str) -> Result<String, String> {
+    if !name.starts_with(|c: char| c.is_ascii_uppercase())
+        || !name.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        return Err(format!("side effect {name} must be in PascalCase"));
+    }
+    let mut constant = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if i > 0 && c.is_ascii_uppercase() {
+            constant.push('_');
+        }
+        constant.push(c.to_ascii_uppercase());
+    }
+    Ok(constant)
+}
@@ -28,4 +28,211 @@ pub enum LootGoblinError {
 
     #[msg("Too few players.")]
     TooFewPlayers,
+
+    #[msg("The game hasn't ended yet.")]
+    GameNotEnded,
+
+    #[msg("Player ratings have already been settled for this game.")]
+    RatingsAlreadySettled,
+
+    #[msg("Rated games need at least two human players.")]
+    NotEnoughHumanPlayers,
+
+    #[msg("Player profile doesn't belong to the expected player.")]
+    InvalidPlayerProfile,
+
+    #[msg("The match queue is full.")]
+    QueueFull,
+
+    #[msg("Player is already in the match queue.")]
+    AlreadyQueued,
+
+    #[msg("Player isn't in the match queue.")]
+    NotQueued,
+
+    #[msg("No players with similar ratings are waiting in the queue.")]
+    NoMatchFound,
+
+    #[msg("Not in the correct tournament phase to perform this action")]
+    WrongTournamentPhase,
+
+    #[msg("The tournament is full.")]
+    TournamentFull,
+
+    #[msg("Player has already joined the tournament.")]
+    AlreadyJoined,
+
+    #[msg("Every table has already been created for this round.")]
+    TablesAlreadyCreated,
+
+    #[msg("Table game doesn't belong to the current tournament round.")]
+    InvalidTable,
+
+    #[msg("Invalid tournament setting.")]
+    InvalidTournamentSetting,
+
+    #[msg("Prize recipient doesn't match the tournament standings.")]
+    InvalidPrizeRecipient,
+
+    #[msg("The game registry is full.")]
+    RegistryFull,
+
+    #[msg("Only games that haven't started or have ended can be closed.")]
+    GameInProgress,
+
+    #[msg("Session expiry must be in the future and within the max session duration.")]
+    InvalidSessionExpiry,
+
+    #[msg("Player doesn't control a goblin in this game.")]
+    NotInGame,
+
+    #[msg("Betting is closed for this game.")]
+    BettingClosed,
+
+    #[msg("Invalid goblin index.")]
+    InvalidGoblinIndex,
+
+    #[msg("Bet amount must be greater than zero.")]
+    InvalidBetAmount,
+
+    #[msg("Side bets haven't been settled yet.")]
+    BetsNotSettled,
+
+    #[msg("Side bets have already been settled.")]
+    BetsAlreadySettled,
+
+    #[msg("This bet didn't win anything.")]
+    NothingToClaim,
+
+    #[msg("Only a goblin's most valuable loot can be claimed.")]
+    LootNotClaimable,
+
+    #[msg("That trade isn't allowed.")]
+    InvalidTrade,
+
+    #[msg("This goblin already has a trade open.")]
+    TradeAlreadyOpen,
+
+    #[msg("This goblin has no open trade.")]
+    NoOpenTrade,
+
+    #[msg("The goblin no longer has what the trade asked for.")]
+    TradeNoLongerValid,
+
+    #[msg("A goblin has no room for what the trade gives it.")]
+    NoRoomForTrade,
+
+    #[msg("There's no hero nearby at that index.")]
+    InvalidHero,
+
+    #[msg("There's no loot at that index.")]
+    InvalidLootIndex,
+
+    #[msg("The hero wants more valuable loot than that.")]
+    BribeTooSmall,
+
+    #[msg("That item isn't in the shop.")]
+    ItemNotAvailable,
+
+    #[msg("The goblin has no room for that item.")]
+    InventoryFull,
+
+    #[msg("That loot isn't worth enough.")]
+    NotEnoughLoot,
+
+    #[msg("There's no item in that inventory slot.")]
+    InvalidItemSlot,
+
+    #[msg("That isn't a goblin class.")]
+    InvalidClass,
+
+    #[msg("The goblin has already used its class ability this game.")]
+    AbilityAlreadyUsed,
+
+    #[msg("There's no passage to that room.")]
+    InvalidRoom,
+
+    #[msg("The goblin has already moved this turn.")]
+    AlreadyMoved,
+
+    #[msg("That isn't a game mode.")]
+    InvalidGameMode,
+
+    #[msg("Team games need a full table of goblins.")]
+    NotEnoughGoblinsForTeams,
+
+    #[msg("That goblin can't be targeted.")]
+    InvalidTarget,
+
+    #[msg("Only competitive games have wagered slap fights.")]
+    WagersNotAllowed,
+
+    #[msg("The deadline for that has passed.")]
+    WagerDeadlinePassed,
+
+    #[msg("The goblin has already committed a wager.")]
+    WagerAlreadyCommitted,
+
+    #[msg("The goblin has no wager to reveal.")]
+    WagerNotCommitted,
+
+    #[msg("Wagers can't be revealed until every goblin has committed or time runs out.")]
+    RevealsNotOpen,
+
+    #[msg("The revealed wager doesn't match the commitment.")]
+    WagerMismatch,
+
+    #[msg("The slap fight isn't over yet.")]
+    SlapFightNotOver,
+
+    #[msg("Those fainting rules aren't allowed.")]
+    InvalidFaintRules,
+
+    #[msg("CPU goblins don't earn achievements.")]
+    NoAchievementsForCpu,
+
+    #[msg("A season has to end after it starts.")]
+    InvalidSeasonSetting,
+
+    #[msg("The game didn't end during the season.")]
+    OutsideSeason,
+
+    #[msg("The game has already been recorded in its season.")]
+    GameAlreadyRecorded,
+
+    #[msg("The game has already been entered into a season.")]
+    GameAlreadyInSeason,
+
+    #[msg("The game wasn't entered into this season.")]
+    GameNotInSeason,
+
+    #[msg("The season hasn't ended yet.")]
+    SeasonNotOver,
+
+    #[msg("The season has already been closed.")]
+    SeasonClosed,
+
+    #[msg("A series has to be an odd number of games, at least 3 and at most 9.")]
+    InvalidSeriesLength,
+
+    #[msg("The series is already over.")]
+    SeriesOver,
+
+    #[msg("Side bet markets on the game have to be settled before a rematch.")]
+    MarketsStillOpen,
+
+    #[msg("Games with side bet markets or claimed loot NFTs can't be closed.")]
+    GameHasLinkedAccounts,
+
+    #[msg("The game has already been migrated.")]
+    GameAlreadyMigrated,
+
+    #[msg("Only bets on games that were closed or abandoned can be cancelled.")]
+    GameNotAbandoned,
+
+    #[msg("The same player profile can only be rated once per game.")]
+    DuplicatePlayerProfile,
+
+    #[msg("The turn has more events than take_turn can play, so play it step by step.")]
+    TooManyPlannedEvents,
 }
@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+/// Emitted whenever the outcome of an event is determined.
+#[event]
+pub struct OutcomeDetermined {
+    pub game: Pubkey,
+    pub goblin: u8,
+    pub turn: u8,
+    pub event: u8,
+    pub choice: u8,
+    pub risk_it: bool,
+    pub outcome: u8,       // EventOutcome
+    pub score_percent: u8, // the goblin's score multiplier after the choice
+}
+
+/// Emitted when a player's profile records an achievement for the first time.
+#[event]
+pub struct AchievementUnlocked {
+    pub player: Pubkey,
+    pub game: Pubkey,
+    pub achievement: u8, // Achievement
+}
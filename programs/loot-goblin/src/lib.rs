@@ -1,7 +1,14 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::{self, Metadata};
+use anchor_spl::token::{self, spl_token::instruction::AuthorityType, Mint, Token, TokenAccount};
+use mpl_token_metadata::state::DataV2;
 pub use state::*;
 
 mod error;
+mod events;
 mod seeds;
 mod state;
 mod utils;
@@ -12,20 +19,153 @@ declare_id!("9yCzP1smsGZmbNyTn87bgLM8wGdj5GcomXZHfQ8JXggZ");
 pub mod loot_goblin {
     use super::*;
     use error::LootGoblinError;
-    use state::{EventOutcome, Goblin};
+    use events::{AchievementUnlocked, OutcomeDetermined};
+    use state::{
+        Achievement, EventSummary, FaintRules, GameMode, GoblinClass, LegacyGame, Player, Season,
+        Session, Tournament, TradeOffer, TurnPlan, TurnSummary,
+    };
     use utils::Dice;
 
     /// Initialize a new [Game].
-    pub fn create_game(ctx: Context<CreateGame>, game_id: u8, game_rounds: u8) -> Result<()> {
+    pub fn create_game(
+        ctx: Context<CreateGame>,
+        game_id: u8,
+        game_rounds: u8,
+        game_mode: u8,
+        faint_rules: FaintRules,
+    ) -> Result<()> {
         let game = &mut ctx.accounts.game;
         let mut game = game.load_init()?;
         // Assign creator, bump, and id
         game.creator = ctx.accounts.creator.key();
         game.bump = *ctx.bumps.get("game").unwrap();
         game.id = game_id;
+        game.seed_scheme = Game::SEED_SCHEME_LEGACY;
+        game.created_at = Clock::get()?.unix_timestamp;
+        game.created_slot = Clock::get()?.slot;
+        // Assign some initial values to game state
+        if GameMode::from_u8(game_mode).is_none() {
+            return err!(LootGoblinError::InvalidGameMode);
+        }
+        game.game_mode = game_mode;
+        if !faint_rules.is_valid() {
+            return err!(LootGoblinError::InvalidFaintRules);
+        }
+        game.faint_rules = faint_rules;
+        game.game_rounds = game_rounds;
+        game.game_phase = Game::GAME_PHASE_RECRUIT_GOBLINS;
+        Ok(())
+    }
+
+    /// Initialize the [GameCounter] that hands out the creator's game ids.
+    pub fn create_game_counter(ctx: Context<CreateGameCounter>) -> Result<()> {
+        let game_counter = &mut ctx.accounts.game_counter;
+        game_counter.bump = *ctx.bumps.get("game_counter").unwrap();
+        game_counter.creator = ctx.accounts.creator.key();
+        Ok(())
+    }
+
+    /// Initialize a new [Game] using the creator's next game id, listing it in the
+    /// [GameRegistry] if one is provided.
+    pub fn create_next_game(
+        ctx: Context<CreateNextGame>,
+        game_rounds: u8,
+        game_mode: u8,
+        faint_rules: FaintRules,
+    ) -> Result<()> {
+        let game_key = ctx.accounts.game.key();
+        let mut game = ctx.accounts.game.load_init()?;
+        // Assign creator, bump, and id
+        game.creator = ctx.accounts.creator.key();
+        game.bump = *ctx.bumps.get("game").unwrap();
+        game.wide_id = ctx.accounts.game_counter.take_next_id();
+        game.seed_scheme = Game::SEED_SCHEME_WIDE;
+        game.created_at = Clock::get()?.unix_timestamp;
+        game.created_slot = Clock::get()?.slot;
         // Assign some initial values to game state
+        if GameMode::from_u8(game_mode).is_none() {
+            return err!(LootGoblinError::InvalidGameMode);
+        }
+        game.game_mode = game_mode;
+        if !faint_rules.is_valid() {
+            return err!(LootGoblinError::InvalidFaintRules);
+        }
+        game.faint_rules = faint_rules;
         game.game_rounds = game_rounds;
         game.game_phase = Game::GAME_PHASE_RECRUIT_GOBLINS;
+        // Make the game discoverable
+        if let Some(game_registry) = &mut ctx.accounts.game_registry {
+            game_registry.list(game_key)?;
+        }
+        Ok(())
+    }
+
+    /// Initialize the global [GameRegistry] of open games.
+    pub fn create_game_registry(ctx: Context<CreateGameRegistry>) -> Result<()> {
+        let game_registry = &mut ctx.accounts.game_registry;
+        game_registry.bump = *ctx.bumps.get("game_registry").unwrap();
+        Ok(())
+    }
+
+    /// Remove a game from the [GameRegistry]. Games that are still recruiting can only be
+    /// unlisted by their creator, or by anyone once they've been listed for too long.
+    pub fn unlist_game(ctx: Context<UnlistGame>) -> Result<()> {
+        let game = ctx.accounts.game.load()?;
+        if game.is_open()
+            && ctx.accounts.signer.key() != game.creator
+            && Clock::get()?.unix_timestamp < game.created_at + Game::LISTING_DURATION
+        {
+            return err!(LootGoblinError::WrongGamePhase);
+        }
+        let game_key = ctx.accounts.game.key();
+        ctx.accounts.game_registry.unlist(game_key);
+        Ok(())
+    }
+
+    /// Close a game that hasn't started or has ended, freeing its id and rent. Takes the
+    /// [GameRegistry] so the game can't be left listed once it's gone.
+    pub fn close_game(ctx: Context<CloseGame>) -> Result<()> {
+        let game = ctx.accounts.game.load()?;
+        // Check if signer is game creator
+        if ctx.accounts.creator.key() != game.creator {
+            return err!(LootGoblinError::InvalidAuthority);
+        }
+        // Check game phase
+        if !game.is_open() && game.game_phase != Game::GAME_PHASE_CRAWL_ENDED {
+            return err!(LootGoblinError::GameInProgress);
+        }
+        // A new game could take over the address its markets and loot NFTs are derived from
+        if game.has_linked_accounts() {
+            return err!(LootGoblinError::GameHasLinkedAccounts);
+        }
+        ctx.accounts.game_registry.unlist(ctx.accounts.game.key());
+        Ok(())
+    }
+
+    /// Convert a [Game] created before the account layout grew, resizing it to fit.
+    /// Such games can't be loaded by any other instruction until they're migrated.
+    pub fn migrate_game(ctx: Context<MigrateGame>) -> Result<()> {
+        let game_info = ctx.accounts.game.to_account_info();
+        let legacy = {
+            let data = game_info.try_borrow_data()?;
+            if data.len() < 8 || data[..8] != Game::DISCRIMINATOR {
+                return err!(ErrorCode::AccountDiscriminatorMismatch);
+            }
+            if data.len() >= 8 + std::mem::size_of::<Game>() {
+                return err!(LootGoblinError::GameAlreadyMigrated);
+            }
+            *bytemuck::from_bytes::<LegacyGame>(&data[8..8 + std::mem::size_of::<LegacyGame>()])
+        };
+        utils::resize_account(
+            &game_info,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            Game::SIZE,
+        )?;
+        let mut data = game_info.try_borrow_mut_data()?;
+        let game: &mut Game =
+            bytemuck::from_bytes_mut(&mut data[8..8 + std::mem::size_of::<Game>()]);
+        game.migrate(&legacy);
         Ok(())
     }
 
@@ -34,6 +174,7 @@ pub mod loot_goblin {
         num_goblins: u8,
         players: Vec<Pubkey>,
     ) -> Result<()> {
+        let game_key = ctx.accounts.game.key();
         let game = &mut ctx.accounts.game;
         let mut game = game.load_mut()?;
         // Check if signer is game creator
@@ -44,26 +185,33 @@ pub mod loot_goblin {
         if game.game_phase != Game::GAME_PHASE_RECRUIT_GOBLINS {
             return err!(LootGoblinError::WrongGamePhase);
         }
-        // Check num players
-        let num_players = players.len();
-        if num_players < Game::MIN_PLAYERS {
-            return err!(LootGoblinError::TooFewPlayers);
+        game.recruit(num_goblins, &players)?;
+        // The game is no longer open
+        if let Some(game_registry) = &mut ctx.accounts.game_registry {
+            game_registry.unlist(game_key);
+        }
+        Ok(())
+    }
+
+    /// Choose the class of the goblin at `goblin_index` before the crawl starts.
+    pub fn choose_class(ctx: Context<ChooseClass>, goblin_index: u8, class: u8) -> Result<()> {
+        let mut game = ctx.accounts.game.load_mut()?;
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_FIND_GREEDIEST {
+            return err!(LootGoblinError::WrongGamePhase);
         }
-        if num_players > Game::MAX_PLAYERS {
-            return err!(LootGoblinError::TooManyPlayers);
+        if goblin_index >= game.num_goblins {
+            return err!(LootGoblinError::InvalidGoblinIndex);
         }
-        if num_players > num_goblins as usize {
-            return err!(LootGoblinError::TooManyPlayers);
+        // Ensure goblin can be controlled by signer
+        let goblin = &mut game.goblins[goblin_index as usize];
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), None) {
+            return err!(LootGoblinError::InvalidAuthority);
         }
-        game.num_goblins = num_goblins;
-        // Init goblins
-        let mut i = 0;
-        for player in players {
-            game.goblins[i].init(player);
-            i += 1;
+        if GoblinClass::from_u8(class).is_none() {
+            return err!(LootGoblinError::InvalidClass);
         }
-        // Move to next phrase
-        game.game_phase = Game::GAME_PHASE_FIND_GREEDIEST;
+        goblin.class = class;
         Ok(())
     }
 
@@ -74,35 +222,58 @@ pub mod loot_goblin {
         if game.game_phase != Game::GAME_PHASE_FIND_GREEDIEST {
             return err!(LootGoblinError::WrongGamePhase);
         }
-        // Get a unique roll per goblin
-        let mut dice = Dice::new();
-        let rolls = dice.roll_unique(Dice::D10, game.num_goblins as usize);
-        // Update goblin greed
-        let mut max_greed = 0;
-        let mut max_index = 0;
-        for (i, greed) in rolls.iter().enumerate() {
-            game.goblins[i].greed = *greed;
-            game.goblins[i].last_roll = *greed;
-            game.goblins[i].last_roll_at = game.turn_count;
-            if *greed > max_greed {
-                max_greed = *greed;
-                max_index = i;
-            }
-        }
-        // Let the crawl commence!
-        game.game_phase = Game::GAME_PHASE_CRAWL_STARTED;
-        // The greediest goblin goes first
-        game.turn_goblin = max_index as u8;
-        game.start_turn();
+        game.find_greediest();
         Ok(())
     }
 
+    /// Play a finished [Game] again with the same players and settings, as the next game
+    /// of a best-of-`best_of` series. Side bet markets on the game have to be settled first,
+    /// since its results are wiped.
+    pub fn rematch(ctx: Context<Rematch>, best_of: u8) -> Result<()> {
+        let game = &mut ctx.accounts.game;
+        let mut game = game.load_mut()?;
+        // Check if signer is game creator
+        if ctx.accounts.creator.key() != game.creator {
+            return err!(LootGoblinError::InvalidAuthority);
+        }
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_CRAWL_ENDED {
+            return err!(LootGoblinError::GameNotEnded);
+        }
+        if game.open_markets > 0 {
+            return err!(LootGoblinError::MarketsStillOpen);
+        }
+        game.rematch(best_of)
+    }
+
+    /// Move the turn goblin to a connected room before it rummages.
+    pub fn move_goblin(ctx: Context<MoveGoblin>, room: u8) -> Result<()> {
+        let game = &mut ctx.accounts.game;
+        let mut game = game.load_mut()?;
+        // Ensure goblin can be controlled by signer
+        let goblin = game.get_turn_goblin();
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
+            return err!(LootGoblinError::InvalidAuthority);
+        }
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_CRAWL_STARTED {
+            return err!(LootGoblinError::WrongGamePhase);
+        }
+        // Check turn phase
+        if game.turn_phase != Game::TURN_PHASE_RUMMAGE {
+            return err!(LootGoblinError::WrongTurnPhase);
+        }
+        game.move_goblin(room)
+    }
+
     pub fn rummage_through_loot_sack(ctx: Context<RummageThroughLootSack>) -> Result<()> {
         let game = &mut ctx.accounts.game;
         let mut game = game.load_mut()?;
         // Ensure goblin can be controlled by signer
         let goblin = game.get_turn_goblin();
-        if !goblin.can_be_controlled_by(ctx.accounts.signer.key()) {
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
             return err!(LootGoblinError::InvalidAuthority);
         }
         // Check game phase
@@ -115,18 +286,7 @@ pub mod loot_goblin {
         }
         // Do rummage roll
         let mut dice = Dice::new();
-        if dice.roll(Dice::D10) >= game.rummage_success_min {
-            goblin.add_random_loot(&mut dice);
-        }
-        // Skipping bribe phase for now
-        if true {
-            // Move to outcome phase w new event
-            game.new_random_event(&mut dice);
-            game.turn_phase = Game::TURN_PHASE_OUTCOME;
-            return Ok(());
-        }
-        // Move to bribe phase
-        game.turn_phase = Game::TURN_PHASE_BRIBE;
+        game.rummage(&mut dice);
         Ok(())
     }
 
@@ -140,7 +300,8 @@ pub mod loot_goblin {
         let mut game = game.load_mut()?;
         // Ensure goblin can be controlled by signer
         let goblin = game.get_turn_goblin();
-        if !goblin.can_be_controlled_by(ctx.accounts.signer.key()) {
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
             return err!(LootGoblinError::InvalidAuthority);
         }
         // Check game phase
@@ -151,29 +312,84 @@ pub mod loot_goblin {
         if game.turn_phase != Game::TURN_PHASE_BRIBE {
             return err!(LootGoblinError::WrongTurnPhase);
         }
-        // Check if the player is bribing a hero
-        if did_bribe {
-            // TODO: handle bribe logic
-            // ...
+        let bribe = did_bribe.then_some((hero_index as usize, loot_index as usize));
+        let mut dice = Dice::new();
+        game.bribe_hero(bribe, &mut dice)
+    }
+
+    /// Buy an item from the shop with loot during the rummage or bribe phase.
+    pub fn buy_item(ctx: Context<BuyItem>, shop_index: u8, loot_indices: Vec<u8>) -> Result<()> {
+        let game = &mut ctx.accounts.game;
+        let mut game = game.load_mut()?;
+        // Ensure goblin can be controlled by signer
+        let goblin = game.get_turn_goblin();
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
+            return err!(LootGoblinError::InvalidAuthority);
         }
-        if goblin.held_item == 0 {
-            // Move to outcome phase w new event
-            let mut dice = Dice::new();
-            game.new_random_event(&mut dice);
-            game.turn_phase = Game::TURN_PHASE_OUTCOME;
-            return Ok(());
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_CRAWL_STARTED {
+            return err!(LootGoblinError::WrongGamePhase);
         }
-        // Move to item phase
-        game.turn_phase = Game::TURN_PHASE_ITEM;
-        Ok(())
+        // Check turn phase
+        if game.turn_phase != Game::TURN_PHASE_RUMMAGE && game.turn_phase != Game::TURN_PHASE_BRIBE
+        {
+            return err!(LootGoblinError::WrongTurnPhase);
+        }
+        game.buy_item(shop_index as usize, &loot_indices)
     }
 
-    pub fn use_item(ctx: Context<UseItem>, use_item: bool) -> Result<()> {
+    pub fn use_item(ctx: Context<UseItem>, item_slot: Option<u8>) -> Result<()> {
         let game = &mut ctx.accounts.game;
         let mut game = game.load_mut()?;
         // Ensure goblin can be controlled by signer
         let goblin = game.get_turn_goblin();
-        if !goblin.can_be_controlled_by(ctx.accounts.signer.key()) {
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
+            return err!(LootGoblinError::InvalidAuthority);
+        }
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_CRAWL_STARTED {
+            return err!(LootGoblinError::WrongGamePhase);
+        }
+        // Check turn phase
+        if game.turn_phase != Game::TURN_PHASE_ITEM {
+            return err!(LootGoblinError::WrongTurnPhase);
+        }
+        let mut dice = Dice::new();
+        game.use_item(item_slot.map(|slot| slot as usize), &mut dice)
+    }
+
+    /// Equip the item in the turn goblin's inventory slot `item_slot` during its item phase.
+    pub fn equip_item(ctx: Context<EquipItem>, item_slot: u8) -> Result<()> {
+        let game = &mut ctx.accounts.game;
+        let game = game.load_mut()?;
+        // Ensure goblin can be controlled by signer
+        let goblin = game.get_turn_goblin();
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
+            return err!(LootGoblinError::InvalidAuthority);
+        }
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_CRAWL_STARTED {
+            return err!(LootGoblinError::WrongGamePhase);
+        }
+        // Check turn phase
+        if game.turn_phase != Game::TURN_PHASE_ITEM {
+            return err!(LootGoblinError::WrongTurnPhase);
+        }
+        goblin.equip(item_slot as usize)
+    }
+
+    /// Throw away the items in the turn goblin's inventory slot `item_slot` during its item
+    /// phase.
+    pub fn drop_items(ctx: Context<DropItems>, item_slot: u8) -> Result<()> {
+        let game = &mut ctx.accounts.game;
+        let game = game.load_mut()?;
+        // Ensure goblin can be controlled by signer
+        let goblin = game.get_turn_goblin();
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
             return err!(LootGoblinError::InvalidAuthority);
         }
         // Check game phase
@@ -184,17 +400,29 @@ pub mod loot_goblin {
         if game.turn_phase != Game::TURN_PHASE_ITEM {
             return err!(LootGoblinError::WrongTurnPhase);
         }
-        if use_item {
-            // TODO: handle item usage logic
-            // ...
+        goblin.drop_items(item_slot as usize)
+    }
+
+    /// Use the turn goblin's once-per-game class ability, before it heads into an event.
+    pub fn use_class_ability(ctx: Context<UseClassAbility>) -> Result<()> {
+        let game = &mut ctx.accounts.game;
+        let mut game = game.load_mut()?;
+        // Ensure goblin can be controlled by signer
+        let goblin = game.get_turn_goblin();
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
+            return err!(LootGoblinError::InvalidAuthority);
+        }
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_CRAWL_STARTED {
+            return err!(LootGoblinError::WrongGamePhase);
+        }
+        // Check turn phase
+        if game.turn_phase != Game::TURN_PHASE_RUMMAGE && game.turn_phase != Game::TURN_PHASE_ITEM {
+            return err!(LootGoblinError::WrongTurnPhase);
         }
-        // Move to event phase
-        // game.turn_phase = Game::TURN_PHASE_EVENT;
-        // Move to outcome phase w new event
         let mut dice = Dice::new();
-        game.new_random_event(&mut dice);
-        game.turn_phase = Game::TURN_PHASE_OUTCOME;
-        Ok(())
+        game.use_class_ability(&mut dice)
     }
 
     pub fn trigger_event(ctx: Context<TriggerEvent>) -> Result<()> {
@@ -202,7 +430,8 @@ pub mod loot_goblin {
         let mut game = game.load_mut()?;
         // Ensure goblin can be controlled by signer
         let goblin = unsafe { (*game.ptr()).get_turn_goblin() };
-        if !goblin.can_be_controlled_by(ctx.accounts.signer.key()) {
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
             return err!(LootGoblinError::InvalidAuthority);
         }
         // Check game phase
@@ -219,12 +448,18 @@ pub mod loot_goblin {
         Ok(())
     }
 
-    pub fn determine_outcome(ctx: Context<DetermineOutcome>, choice: u8) -> Result<()> {
+    pub fn determine_outcome(
+        ctx: Context<DetermineOutcome>,
+        choice: u8,
+        risk_it: bool,
+    ) -> Result<()> {
+        let game_key = ctx.accounts.game.key();
         let game = &mut ctx.accounts.game;
         let mut game = game.load_mut()?;
         // Ensure goblin can be controlled by signer
         let goblin = unsafe { (*game.ptr()).get_turn_goblin() };
-        if !goblin.can_be_controlled_by(ctx.accounts.signer.key()) {
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
             return err!(LootGoblinError::InvalidAuthority);
         }
         // Check game phase
@@ -235,155 +470,52 @@ pub mod loot_goblin {
         if game.turn_phase != Game::TURN_PHASE_OUTCOME {
             return err!(LootGoblinError::WrongTurnPhase);
         }
-        // Handle choice side-effects
-        let i = choice as usize % game.event_side_effects.len();
-        match game.event_side_effects[i] {
-            _side_effect_id => {
-                // TODO
-                // ...
-            }
-        }
-        // Make sure goblin didn't faint from side-effects
-        if goblin.health == 0 {
-            // Start the next goblin's turn
-            game.advance_to_next_goblin();
-            game.start_turn();
-            return Ok(());
-        }
-        // Calculate rich tax (richer goblins are less lucky)
-        let total_loot: u8 = goblin.loot_bag.iter().sum();
-        let rich_tax = total_loot / 10;
-        // Set outcome probabilities
-        let mut weights = [0u8; EventOutcome::LEN];
-        // Good stuff
-        weights[EventOutcome::GetLoot as usize] = 10 + goblin.luck;
-        weights[EventOutcome::GetItem as usize] = 1 + goblin.luck;
-        weights[EventOutcome::StealLoot as usize] = 1 + goblin.luck;
-        weights[EventOutcome::StealItem as usize] = 1 + goblin.luck;
-        weights[EventOutcome::Heal as usize] = 1 + goblin.luck;
-        weights[EventOutcome::BoostLuck as usize] = 1 + goblin.greed;
-        weights[EventOutcome::ReduceGreed as usize] = 1 + goblin.greed;
-        // Bad + neutral stuff
-        weights[EventOutcome::LoseLoot as usize] = 10 + goblin.greed + rich_tax;
-        weights[EventOutcome::LoseItem as usize] = 1 + goblin.greed + rich_tax;
-        weights[EventOutcome::LootGotStolen as usize] = 1 + goblin.greed + rich_tax;
-        weights[EventOutcome::ItemGotStolen as usize] = 1 + goblin.greed + rich_tax;
-        weights[EventOutcome::SlapFight as usize] = 1 + goblin.greed + rich_tax;
-        weights[EventOutcome::GetAttacked as usize] = 1 + (goblin.greed * game.turn_events);
-        weights[EventOutcome::OK as usize] = 1 + goblin.luck;
-        // If the goblin isn't risking, reduce reward and make OK outcome very likely
-        // if !risk_it {
-        //     weights[EventOutcome::GetLoot as usize] = 0;
-        //     weights[EventOutcome::GetItem as usize] = 0;
-        //     weights[EventOutcome::StealLoot as usize] = 0;
-        //     weights[EventOutcome::StealItem as usize] = 0;
-        //     weights[EventOutcome::Heal as usize] = 0;
-        //     weights[EventOutcome::BoostLuck as usize] = 0;
-        //     weights[EventOutcome::ReduceGreed as usize] = 0;
-        //     weights[EventOutcome::OK as usize] = 40;
-        // }
-        // Calculate outcome
-        let total_weight: u8 = weights.iter().sum();
         let mut dice = Dice::new();
-        let roll = dice.roll(total_weight);
-        let mut outcome = EventOutcome::OK;
-        let mut offset = 0;
-        for (i, weight) in weights.iter().enumerate() {
-            if roll >= offset && roll < offset + weight {
-                outcome = unsafe { std::mem::transmute(i as u8) };
-                break;
-            }
-            offset += weight;
-        }
-        game.set_event_outcome(outcome);
-        msg!("{:?}", outcome);
-        // Handle outcome
-        match outcome {
-            EventOutcome::GetLoot => {
-                goblin.add_random_loot(&mut dice);
-                game.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
-            }
-            EventOutcome::GetItem => {
-                goblin.add_random_item(&mut dice);
-                game.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
-            }
-            EventOutcome::StealLoot => {
-                let i = dice.roll(game.num_goblins as u8) as usize;
-                let victim = game.get_goblin_mut(i);
-                let loot = victim.take_least_valuable_loot();
-                victim.luck = victim.luck.saturating_add(1);
-                goblin.add_loot(loot);
-                game.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
-            }
-            EventOutcome::StealItem => {
-                let i = dice.roll(game.num_goblins as u8) as usize;
-                let victim = game.get_goblin_mut(i);
-                let item = victim.held_item;
-                victim.held_item = 0;
-                if item > 0 {
-                    goblin.held_item = item;
-                    goblin.greed = goblin.greed.saturating_add(1);
-                }
-                game.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
-            }
-            EventOutcome::Heal => {
-                goblin.health = goblin.health.saturating_add(1).min(Goblin::MAX_HEALTH);
-                game.aftermath_option = Game::AFTERMATH_OPTION_STOP;
-            }
-            EventOutcome::BoostLuck => {
-                goblin.luck = goblin.luck.saturating_add(1);
-                game.aftermath_option = Game::AFTERMATH_OPTION_STOP;
-            }
-            EventOutcome::ReduceGreed => {
-                goblin.greed = goblin.greed.saturating_sub(1);
-                game.aftermath_option = Game::AFTERMATH_OPTION_STOP;
-            }
-            EventOutcome::LoseLoot => {
-                let _loot = goblin.take_least_valuable_loot();
-                game.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
-            }
-            EventOutcome::LoseItem => {
-                goblin.held_item = 0;
-                game.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
-            }
-            EventOutcome::LootGotStolen => {
-                let loot = goblin.take_least_valuable_loot();
-                let i = dice.roll(game.num_goblins as u8) as usize;
-                game.get_goblin_mut(i).add_loot(loot);
-                game.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
-            }
-            EventOutcome::ItemGotStolen => {
-                let thief_index = dice.roll(game.num_goblins as u8) as usize;
-                game.get_goblin_mut(thief_index).held_item = goblin.held_item;
-                goblin.held_item = 0;
-                game.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
-            }
-            EventOutcome::SlapFight => {
-                // Immediately move to slap fight phase
-                game.turn_phase = Game::TURN_PHASE_SLAP_FIGHT;
-                return Ok(());
-            }
-            EventOutcome::GetAttacked => {
-                goblin.health = goblin.health.saturating_sub(1);
-                let _loot = goblin.take_least_valuable_loot();
-                game.aftermath_option = Game::AFTERMATH_OPTION_STOP;
-            }
-            EventOutcome::OK => {
-                // Nothing happens! :)
-                game.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
-            }
+        let (goblin_index, turn, event) = (game.turn_goblin, game.turn_count, game.event);
+        if let Some(outcome) = game.determine_outcome(choice, risk_it, &mut dice) {
+            emit!(OutcomeDetermined {
+                game: game_key,
+                goblin: goblin_index,
+                turn,
+                event,
+                choice,
+                risk_it,
+                outcome: outcome as u8,
+                score_percent: goblin.score_percent(),
+            });
         }
-        // Move to the aftermath phase
-        game.turn_phase = Game::TURN_PHASE_AFTERMATH;
         Ok(())
     }
 
+    /// Choose which goblin to steal from after winning a steal.
+    pub fn choose_target(ctx: Context<ChooseTarget>, target: u8) -> Result<()> {
+        let game = &mut ctx.accounts.game;
+        let mut game = game.load_mut()?;
+        // Ensure goblin can be controlled by signer
+        let goblin = game.get_turn_goblin();
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
+            return err!(LootGoblinError::InvalidAuthority);
+        }
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_CRAWL_STARTED {
+            return err!(LootGoblinError::WrongGamePhase);
+        }
+        // Check turn phase
+        if game.turn_phase != Game::TURN_PHASE_CHOOSE_TARGET {
+            return err!(LootGoblinError::WrongTurnPhase);
+        }
+        let mut dice = Dice::new();
+        game.choose_target(target as usize, &mut dice)
+    }
+
     pub fn make_aftermath_decision(ctx: Context<MakeAftermathDecision>, choice: u8) -> Result<()> {
         let game = &mut ctx.accounts.game;
         let mut game = game.load_mut()?;
         // Ensure goblin can be controlled by signer
         let goblin = unsafe { (*game.ptr()).get_turn_goblin() };
-        if !goblin.can_be_controlled_by(ctx.accounts.signer.key()) {
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
             return err!(LootGoblinError::InvalidAuthority);
         }
         // Check game phase
@@ -394,26 +526,9 @@ pub mod loot_goblin {
         if game.turn_phase != Game::TURN_PHASE_AFTERMATH {
             return err!(LootGoblinError::WrongTurnPhase);
         }
-        // Continue or stop
-        match (game.aftermath_option, choice) {
-            (Game::AFTERMATH_OPTION_CONTINUE, _)
-            | (Game::AFTERMATH_OPTION_EITHER, Game::AFTERMATH_OPTION_CONTINUE) => {
-                if goblin.held_item == 0 {
-                    let mut dice = Dice::new();
-                    // Move to outcome phase w new event
-                    game.new_random_event(&mut dice);
-                    game.turn_phase = Game::TURN_PHASE_OUTCOME;
-                    return Ok(());
-                }
-                game.turn_phase = Game::TURN_PHASE_ITEM;
-                return Ok(());
-            }
-            _ => {
-                game.advance_to_next_goblin();
-                game.start_turn();
-                return Ok(());
-            }
-        }
+        let mut dice = Dice::new();
+        game.make_aftermath_decision(choice, &mut dice);
+        Ok(())
     }
 
     pub fn slap_fight(ctx: Context<SlapFight>) -> Result<()> {
@@ -421,7 +536,8 @@ pub mod loot_goblin {
         let mut game = game.load_mut()?;
         // Ensure goblin can be controlled by signer
         let goblin = unsafe { (*game.ptr()).get_turn_goblin() };
-        if !goblin.can_be_controlled_by(ctx.accounts.signer.key()) {
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
             return err!(LootGoblinError::InvalidAuthority);
         }
         // Check game phase
@@ -432,107 +548,2102 @@ pub mod loot_goblin {
         if game.turn_phase != Game::TURN_PHASE_SLAP_FIGHT {
             return err!(LootGoblinError::WrongTurnPhase);
         }
-        // Each goblin rolls, find the highest and lowest rolls
         let mut dice = Dice::new();
-        let rolls = dice.roll_unique(Dice::D10, game.num_goblins as usize);
-        let mut highest_roll = 0;
-        let mut lowest_roll = u8::MAX;
-        let mut highest_goblin_index = 0;
-        let mut lowest_goblin_index = 0;
-        for (i, roll) in rolls.iter().enumerate() {
-            game.goblins[i].last_roll = *roll;
-            game.goblins[i].last_roll_at = game.turn_count;
-            if *roll > highest_roll {
-                highest_roll = *roll;
-                highest_goblin_index = i;
-            }
-            if *roll < lowest_roll {
-                lowest_roll = *roll;
-                lowest_goblin_index = i;
-            }
+        game.slap_fight(&mut dice);
+        Ok(())
+    }
+
+    /// Turn the pending slap fight into a wagered one that every goblin commits to.
+    pub fn open_wagered_slap_fight(ctx: Context<OpenWageredSlapFight>) -> Result<()> {
+        let game_key = ctx.accounts.game.key();
+        let mut game = ctx.accounts.game.load_mut()?;
+        // Ensure goblin can be controlled by signer
+        let goblin = game.get_turn_goblin();
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
+            return err!(LootGoblinError::InvalidAuthority);
         }
-        // Increase all goblin greed
-        for goblin in &mut game.goblins {
-            goblin.greed = goblin.greed.saturating_add(1);
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_CRAWL_STARTED {
+            return err!(LootGoblinError::WrongGamePhase);
         }
-        // The goblin with the highest roll takes loot from the one with the lowest roll
-        if highest_goblin_index != lowest_goblin_index {
-            let loot = game.goblins[lowest_goblin_index].take_least_valuable_loot();
-            game.goblins[highest_goblin_index].add_loot(loot);
-            // decrease loser greed
-            game.goblins[lowest_goblin_index].greed =
-                game.goblins[lowest_goblin_index].greed.saturating_sub(1);
-            // Increase winner greed
-            game.goblins[highest_goblin_index].greed =
-                game.goblins[highest_goblin_index].greed.saturating_add(1);
+        // Check turn phase
+        if game.turn_phase != Game::TURN_PHASE_SLAP_FIGHT {
+            return err!(LootGoblinError::WrongTurnPhase);
         }
-
-        // Start the next goblin's turn
-        game.advance_to_next_goblin();
-        game.start_turn();
+        game.open_wagered_slap_fight()?;
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.slap_fight.init(
+            *ctx.bumps.get("slap_fight").unwrap(),
+            game_key,
+            &game,
+            ctx.accounts.signer.key(),
+            now,
+        );
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-#[instruction(game_id: u8, game_rounds: u8)]
-pub struct CreateGame<'info> {
-    #[account(mut)]
-    pub creator: Signer<'info>,
-    #[account(
-        init,
-        payer = creator,
-        space = Game::SIZE,
-        seeds = [seeds::GAME, creator.key().as_ref(), &[game_id]],
-        bump,
-    )]
-    pub game: AccountLoader<'info, Game>,
-    pub system_program: Program<'info, System>,
-}
+    /// Commit to a hidden wager for the goblin at `goblin_index`, see
+    /// [WageredSlapFight::commitment].
+    pub fn commit_slap_wager(
+        ctx: Context<CommitSlapWager>,
+        goblin_index: u8,
+        commitment: [u8; 32],
+    ) -> Result<()> {
+        let game = ctx.accounts.game.load()?;
+        if goblin_index >= game.num_goblins {
+            return err!(LootGoblinError::InvalidGoblinIndex);
+        }
+        // Ensure goblin can be controlled by signer
+        let goblin = &game.goblins[goblin_index as usize];
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
+            return err!(LootGoblinError::InvalidAuthority);
+        }
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts
+            .slap_fight
+            .commit(goblin_index as usize, commitment, now)
+    }
 
-#[derive(Accounts)]
-#[instruction(num_goblins: u8, players: Vec<Pubkey>)]
-pub struct RecruitGoblins<'info> {
-    #[account(mut)]
-    pub creator: Signer<'info>,
-    #[account(
-        mut,
-        seeds = [
-            seeds::GAME,
-            creator.key().as_ref(),
-            &[game.load()?.id],
-        ],
-        bump = game.load()?.bump,
-    )]
-    pub game: AccountLoader<'info, Game>,
-}
+    /// Reveal the goblin's committed wager, moving it into the pot.
+    pub fn reveal_slap_wager(
+        ctx: Context<RevealSlapWager>,
+        goblin_index: u8,
+        loot_index: Option<u8>,
+        item_slot: Option<u8>,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        let mut game = ctx.accounts.game.load_mut()?;
+        if goblin_index >= game.num_goblins {
+            return err!(LootGoblinError::InvalidGoblinIndex);
+        }
+        // Ensure goblin can be controlled by signer
+        let goblin = &game.goblins[goblin_index as usize];
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
+            return err!(LootGoblinError::InvalidAuthority);
+        }
+        let now = Clock::get()?.unix_timestamp;
+        let slap_fight = &mut ctx.accounts.slap_fight;
+        if !slap_fight.is_revealing(game.num_goblins, now) {
+            return err!(LootGoblinError::RevealsNotOpen);
+        }
+        let goblin = &mut game.goblins[goblin_index as usize];
+        slap_fight.reveal(
+            goblin_index as usize,
+            goblin,
+            loot_index,
+            item_slot,
+            &salt,
+            now,
+        )
+    }
 
-#[derive(Accounts)]
-pub struct FindGreediestGoblin<'info> {
-    #[account(mut)]
-    pub creator: Signer<'info>,
-    #[account(
-        mut,
-        seeds = [
+    /// Settle a wagered slap fight once every wager is revealed or time runs out.
+    pub fn resolve_wagered_slap_fight(ctx: Context<ResolveWageredSlapFight>) -> Result<()> {
+        let mut game = ctx.accounts.game.load_mut()?;
+        let slap_fight = &ctx.accounts.slap_fight;
+        let now = Clock::get()?.unix_timestamp;
+        if !slap_fight.is_over(game.num_goblins, now) {
+            return err!(LootGoblinError::SlapFightNotOver);
+        }
+        let mut dice = Dice::new();
+        game.resolve_wagered_slap_fight(slap_fight, &mut dice);
+        Ok(())
+    }
+
+    /// Play the turn goblin's whole turn in one transaction, following a [TurnPlan].
+    /// Fails without playing anything if the turn runs into more than
+    /// [Game::MAX_PLANNED_EVENTS] events.
+    pub fn take_turn(ctx: Context<TakeTurn>, plan: TurnPlan) -> Result<TurnSummary> {
+        let game_key = ctx.accounts.game.key();
+        let game = &mut ctx.accounts.game;
+        let mut game = game.load_mut()?;
+        // Ensure goblin can be controlled by signer
+        let goblin = unsafe { (*game.ptr()).get_turn_goblin() };
+        if !goblin.can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
+            return err!(LootGoblinError::InvalidAuthority);
+        }
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_CRAWL_STARTED {
+            return err!(LootGoblinError::WrongGamePhase);
+        }
+        // Check turn phase
+        if game.turn_phase != Game::TURN_PHASE_RUMMAGE {
+            return err!(LootGoblinError::WrongTurnPhase);
+        }
+        let mut summary = TurnSummary {
+            goblin: game.turn_goblin,
+            score_before: goblin.score(),
+            ..Default::default()
+        };
+        // Share one dice across every step so rolls don't repeat
+        let mut dice = Dice::new();
+        let turn_count = game.turn_count;
+        if let Some(room) = plan.move_to {
+            game.move_goblin(room)?;
+        }
+        game.rummage(&mut dice);
+        // Keep stepping until the turn passes to the next goblin
+        while game.game_phase == Game::GAME_PHASE_CRAWL_STARTED && game.turn_count == turn_count {
+            match game.turn_phase {
+                Game::TURN_PHASE_BRIBE => {
+                    let bribe = plan
+                        .bribe
+                        .map(|bribe| (bribe.hero_index as usize, bribe.loot_index as usize));
+                    game.bribe_hero(bribe, &mut dice)?
+                }
+                Game::TURN_PHASE_ITEM => {
+                    // Skip using the item once the planned slot runs out
+                    let slot = plan
+                        .use_item_slot
+                        .map(|slot| slot as usize)
+                        .filter(|slot| goblin.has_item_in(*slot));
+                    game.use_item(slot, &mut dice)?
+                }
+                Game::TURN_PHASE_OUTCOME => {
+                    // Roll the whole turn back rather than leave it half played
+                    if summary.events.len() >= Game::MAX_PLANNED_EVENTS {
+                        return err!(LootGoblinError::TooManyPlannedEvents);
+                    }
+                    let event = game.event;
+                    let choice = plan.choice(summary.events.len());
+                    let risk_it = plan.risk_it.unwrap_or_else(|| game.cpu_should_risk_it());
+                    let outcome = game.determine_outcome(choice, risk_it, &mut dice);
+                    if let Some(outcome) = outcome {
+                        emit!(OutcomeDetermined {
+                            game: game_key,
+                            goblin: summary.goblin,
+                            turn: turn_count,
+                            event,
+                            choice,
+                            risk_it,
+                            outcome: outcome as u8,
+                            score_percent: goblin.score_percent(),
+                        });
+                    }
+                    summary.events.push(EventSummary {
+                        event,
+                        choice,
+                        risk_it,
+                        outcome: outcome.map(|outcome| outcome as u8),
+                    });
+                }
+                Game::TURN_PHASE_AFTERMATH => {
+                    let stop = plan.should_stop(game.turn_events, goblin.health)
+                        || summary.events.len() >= Game::MAX_PLANNED_EVENTS;
+                    let choice = if stop {
+                        Game::AFTERMATH_OPTION_STOP
+                    } else {
+                        Game::AFTERMATH_OPTION_CONTINUE
+                    };
+                    game.make_aftermath_decision(choice, &mut dice);
+                }
+                Game::TURN_PHASE_SLAP_FIGHT => {
+                    summary.slap_fight = true;
+                    game.slap_fight(&mut dice);
+                }
+                Game::TURN_PHASE_CHOOSE_TARGET => {
+                    let target = plan
+                        .target
+                        .map(|target| target as usize)
+                        .filter(|target| game.is_target(*target))
+                        .or_else(|| game.random_opponent(&mut dice));
+                    match target {
+                        Some(target) => game.choose_target(target, &mut dice)?,
+                        None => break,
+                    }
+                }
+                _ => break,
+            }
+        }
+        summary.turn_ended =
+            game.game_phase != Game::GAME_PHASE_CRAWL_STARTED || game.turn_count != turn_count;
+        summary.score_after = goblin.score();
+        summary.health = goblin.health;
+        Ok(summary)
+    }
+
+    /// Offer a trade to another goblin between turns, escrowing what's offered.
+    pub fn offer_trade(
+        ctx: Context<OfferTrade>,
+        goblin_index: u8,
+        offer: TradeOffer,
+    ) -> Result<()> {
+        let mut game = ctx.accounts.game.load_mut()?;
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_CRAWL_STARTED {
+            return err!(LootGoblinError::WrongGamePhase);
+        }
+        // Trades happen before the turn goblin starts rummaging
+        if game.turn_phase != Game::TURN_PHASE_RUMMAGE {
+            return err!(LootGoblinError::WrongTurnPhase);
+        }
+        if goblin_index >= game.num_goblins {
+            return err!(LootGoblinError::InvalidGoblinIndex);
+        }
+        // Ensure goblin can be controlled by signer
+        if !game.goblins[goblin_index as usize]
+            .can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
+            return err!(LootGoblinError::InvalidAuthority);
+        }
+        game.offer_trade(goblin_index as usize, &offer)
+    }
+
+    /// Accept the trade offered by the goblin at `goblin_index`.
+    pub fn accept_trade(ctx: Context<AcceptTrade>, goblin_index: u8) -> Result<()> {
+        let mut game = ctx.accounts.game.load_mut()?;
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_CRAWL_STARTED {
+            return err!(LootGoblinError::WrongGamePhase);
+        }
+        // Trades happen before the turn goblin starts rummaging
+        if game.turn_phase != Game::TURN_PHASE_RUMMAGE {
+            return err!(LootGoblinError::WrongTurnPhase);
+        }
+        if goblin_index >= game.num_goblins {
+            return err!(LootGoblinError::InvalidGoblinIndex);
+        }
+        // Ensure the goblin the trade was offered to can be controlled by signer
+        let trade = game.trades[goblin_index as usize];
+        if !trade.is_open() {
+            return err!(LootGoblinError::NoOpenTrade);
+        }
+        if !game.goblins[trade.with_goblin as usize]
+            .can_be_controlled_by(ctx.accounts.signer.key(), ctx.accounts.session.as_deref())
+        {
+            return err!(LootGoblinError::InvalidAuthority);
+        }
+        game.accept_trade(goblin_index as usize)
+    }
+
+    /// Withdraw or decline the trade offered by the goblin at `goblin_index`.
+    pub fn cancel_trade(ctx: Context<CancelTrade>, goblin_index: u8) -> Result<()> {
+        let mut game = ctx.accounts.game.load_mut()?;
+        if goblin_index >= game.num_goblins {
+            return err!(LootGoblinError::InvalidGoblinIndex);
+        }
+        let trade = game.trades[goblin_index as usize];
+        if !trade.is_open() {
+            return err!(LootGoblinError::NoOpenTrade);
+        }
+        // Either side of the trade can cancel it
+        let signer = ctx.accounts.signer.key();
+        let session = ctx.accounts.session.as_deref();
+        if !game.goblins[goblin_index as usize].can_be_controlled_by(signer, session)
+            && !game.goblins[trade.with_goblin as usize].can_be_controlled_by(signer, session)
+        {
+            return err!(LootGoblinError::InvalidAuthority);
+        }
+        game.cancel_trade(goblin_index as usize)
+    }
+
+    /// Delegate control of the signer's goblin to a throwaway session key until `expires_at`.
+    pub fn create_session(
+        ctx: Context<CreateSession>,
+        session_key: Pubkey,
+        expires_at: i64,
+    ) -> Result<()> {
+        let player = ctx.accounts.player.key();
+        // Check that the player has a goblin in the game
+        let game = ctx.accounts.game.load()?;
+        let num_goblins = game.num_goblins as usize;
+        if !game.goblins[..num_goblins]
+            .iter()
+            .any(|g| g.player == player)
+        {
+            return err!(LootGoblinError::NotInGame);
+        }
+        // Check expiry
+        let now = Clock::get()?.unix_timestamp;
+        if expires_at <= now || expires_at > now + Session::MAX_DURATION {
+            return err!(LootGoblinError::InvalidSessionExpiry);
+        }
+        let session = &mut ctx.accounts.session;
+        session.bump = *ctx.bumps.get("session").unwrap();
+        session.game = ctx.accounts.game.key();
+        session.game_created_slot = game.created_slot;
+        session.player = player;
+        session.session_key = session_key;
+        session.expires_at = expires_at;
+        Ok(())
+    }
+
+    /// Revoke a session key, returning the session account's rent to the player.
+    pub fn revoke_session(_ctx: Context<RevokeSession>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Initialize a rated [Player] profile for the signer.
+    pub fn create_player(ctx: Context<CreatePlayer>) -> Result<()> {
+        let player = &mut ctx.accounts.player;
+        let bump = *ctx.bumps.get("player").unwrap();
+        player.init(ctx.accounts.authority.key(), bump);
+        Ok(())
+    }
+
+    /// Update the ratings of every human player from a finished [Game]'s final standings.
+    /// Expects each human goblin's [Player] profile as a remaining account, in goblin order.
+    /// Games where a player holds more than one seat can't be rated.
+    pub fn settle_ratings(ctx: Context<SettleRatings>) -> Result<()> {
+        let game = &mut ctx.accounts.game;
+        let mut game = game.load_mut()?;
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_CRAWL_ENDED {
+            return err!(LootGoblinError::GameNotEnded);
+        }
+        if game.ratings_settled != 0 {
+            return err!(LootGoblinError::RatingsAlreadySettled);
+        }
+        // Only games between multiple humans are rated
+        let humans: Vec<usize> = (0..game.num_goblins as usize)
+            .filter(|i| !game.goblins[*i].is_cpu())
+            .collect();
+        if humans.len() < 2 {
+            return err!(LootGoblinError::NotEnoughHumanPlayers);
+        }
+        if ctx.remaining_accounts.len() != humans.len() {
+            return err!(LootGoblinError::MissingExpectedAccount);
+        }
+        let mut profiles = Vec::with_capacity(humans.len());
+        for (n, (i, account)) in humans.iter().zip(ctx.remaining_accounts).enumerate() {
+            // A profile rated twice would have its rating updated twice
+            if ctx.remaining_accounts[..n]
+                .iter()
+                .any(|other| other.key == account.key)
+            {
+                return err!(LootGoblinError::DuplicatePlayerProfile);
+            }
+            let profile: Account<Player> = Account::try_from(account)?;
+            if profile.authority != game.goblins[*i].player {
+                return err!(LootGoblinError::InvalidPlayerProfile);
+            }
+            profiles.push(profile);
+        }
+        // Rate each human against every other human by final score
+        let ratings: Vec<u16> = profiles.iter().map(|profile| profile.rating).collect();
+        let scores: Vec<u16> = humans.iter().map(|i| game.goblins[*i].score()).collect();
+        let winner = game.standings()[0];
+        for (a, profile) in profiles.iter_mut().enumerate() {
+            let results: Vec<(u16, i32)> = (0..humans.len())
+                .filter(|b| *b != a)
+                .map(|b| {
+                    let actual = match scores[a].cmp(&scores[b]) {
+                        std::cmp::Ordering::Greater => 1000,
+                        std::cmp::Ordering::Equal => 500,
+                        std::cmp::Ordering::Less => 0,
+                    };
+                    (ratings[b], actual)
+                })
+                .collect();
+            let change = Player::rating_change(ratings[a], &results);
+            profile.apply_rating_change(change);
+            profile.games_played = profile.games_played.saturating_add(1);
+            if humans[a] == winner {
+                profile.wins = profile.wins.saturating_add(1);
+            }
+            profile.exit(ctx.program_id)?;
+        }
+        game.ratings_settled = 1;
+        Ok(())
+    }
+
+    /// Record the achievements the goblin at `goblin_index` earned in a finished [Game]
+    /// on its player's profile.
+    pub fn record_achievements(ctx: Context<RecordAchievements>, goblin_index: u8) -> Result<()> {
+        let game_key = ctx.accounts.game.key();
+        let game = ctx.accounts.game.load()?;
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_CRAWL_ENDED {
+            return err!(LootGoblinError::GameNotEnded);
+        }
+        if goblin_index >= game.num_goblins {
+            return err!(LootGoblinError::InvalidGoblinIndex);
+        }
+        let goblin = &game.goblins[goblin_index as usize];
+        if goblin.is_cpu() {
+            return err!(LootGoblinError::NoAchievementsForCpu);
+        }
+        if ctx.accounts.player.authority != goblin.player {
+            return err!(LootGoblinError::InvalidPlayerProfile);
+        }
+        let new = ctx.accounts.player.unlock_achievements(goblin.achievements);
+        for achievement in Achievement::ALL {
+            if new & achievement.bit() as u32 != 0 {
+                emit!(AchievementUnlocked {
+                    player: goblin.player,
+                    game: game_key,
+                    achievement: achievement as u8,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Initialize the global [MatchQueue].
+    pub fn create_match_queue(ctx: Context<CreateMatchQueue>) -> Result<()> {
+        let match_queue = &mut ctx.accounts.match_queue;
+        match_queue.bump = *ctx.bumps.get("match_queue").unwrap();
+        Ok(())
+    }
+
+    pub fn join_match_queue(ctx: Context<JoinMatchQueue>) -> Result<()> {
+        let rating = ctx.accounts.player.rating;
+        ctx.accounts
+            .match_queue
+            .join(ctx.accounts.authority.key(), rating)
+    }
+
+    pub fn leave_match_queue(ctx: Context<LeaveMatchQueue>) -> Result<()> {
+        ctx.accounts.match_queue.leave(ctx.accounts.authority.key())
+    }
+
+    /// Pair the `num_players` queued players with the closest ratings into a new [Game].
+    /// Anyone can crank this; the payer funds the new game account.
+    pub fn match_players(
+        ctx: Context<MatchPlayers>,
+        num_players: u8,
+        game_rounds: u8,
+    ) -> Result<()> {
+        let match_queue = &mut ctx.accounts.match_queue;
+        let players = match_queue.take_closest_match(num_players as usize)?;
+        let game = &mut ctx.accounts.game;
+        let mut game = game.load_init()?;
+        // The queue is the creator of matched games
+        game.creator = match_queue.key();
+        game.bump = *ctx.bumps.get("game").unwrap();
+        game.wide_id = match_queue.games_created;
+        game.seed_scheme = Game::SEED_SCHEME_WIDE;
+        game.game_rounds = game_rounds;
+        match_queue.games_created += 1;
+        // Nobody can sign for the queue, so skip straight to the crawl
+        game.recruit(num_players, &players)?;
+        game.find_greediest();
+        Ok(())
+    }
+
+    /// Initialize a new [Tournament], seeding its prize pool from the creator.
+    pub fn create_tournament(
+        ctx: Context<CreateTournament>,
+        tournament_id: u8,
+        game_rounds: u8,
+        advance_per_table: u8,
+        entry_fee: u64,
+        prize_pool: u64,
+    ) -> Result<()> {
+        if advance_per_table == 0 || advance_per_table > Tournament::MAX_ADVANCE_PER_TABLE {
+            return err!(LootGoblinError::InvalidTournamentSetting);
+        }
+        let tournament = &mut ctx.accounts.tournament;
+        tournament.creator = ctx.accounts.creator.key();
+        tournament.bump = *ctx.bumps.get("tournament").unwrap();
+        tournament.id = tournament_id;
+        tournament.game_rounds = game_rounds;
+        tournament.advance_per_table = advance_per_table;
+        tournament.entry_fee = entry_fee;
+        tournament.phase = Tournament::TOURNAMENT_PHASE_REGISTRATION;
+        // Fund the prize pool
+        if prize_pool > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.creator.to_account_info(),
+                        to: ctx.accounts.tournament.to_account_info(),
+                    },
+                ),
+                prize_pool,
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn join_tournament(ctx: Context<JoinTournament>) -> Result<()> {
+        let tournament = &mut ctx.accounts.tournament;
+        // Check tournament phase
+        if tournament.phase != Tournament::TOURNAMENT_PHASE_REGISTRATION {
+            return err!(LootGoblinError::WrongTournamentPhase);
+        }
+        let player = ctx.accounts.player.key();
+        if tournament.players.contains(&player) {
+            return err!(LootGoblinError::AlreadyJoined);
+        }
+        if tournament.players.len() >= Tournament::MAX_ENTRANTS {
+            return err!(LootGoblinError::TournamentFull);
+        }
+        tournament.players.push(player);
+        // Pay the entry fee into the prize pool
+        let entry_fee = tournament.entry_fee;
+        if entry_fee > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.player.to_account_info(),
+                        to: ctx.accounts.tournament.to_account_info(),
+                    },
+                ),
+                entry_fee,
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn start_tournament(ctx: Context<StartTournament>) -> Result<()> {
+        let tournament = &mut ctx.accounts.tournament;
+        // Check tournament phase
+        if tournament.phase != Tournament::TOURNAMENT_PHASE_REGISTRATION {
+            return err!(LootGoblinError::WrongTournamentPhase);
+        }
+        if tournament.players.len() < 2 {
+            return err!(LootGoblinError::TooFewPlayers);
+        }
+        tournament.phase = Tournament::TOURNAMENT_PHASE_IN_PROGRESS;
+        Ok(())
+    }
+
+    /// Create the next table [Game] of the current round and seat its players.
+    pub fn create_tournament_table(ctx: Context<CreateTournamentTable>) -> Result<()> {
+        let tournament = &mut ctx.accounts.tournament;
+        // Check tournament phase
+        if tournament.phase != Tournament::TOURNAMENT_PHASE_IN_PROGRESS {
+            return err!(LootGoblinError::WrongTournamentPhase);
+        }
+        let table_index = tournament.tables.len();
+        if table_index >= tournament.num_tables() {
+            return err!(LootGoblinError::TablesAlreadyCreated);
+        }
+        let players = tournament.table_players(table_index);
+        let game_key = ctx.accounts.game.key();
+        let mut game = ctx.accounts.game.load_init()?;
+        // The tournament is the creator of its table games
+        game.creator = tournament.key();
+        game.bump = *ctx.bumps.get("game").unwrap();
+        game.wide_id = tournament.games_created;
+        game.seed_scheme = Game::SEED_SCHEME_WIDE;
+        game.created_at = Clock::get()?.unix_timestamp;
+        game.created_slot = Clock::get()?.slot;
+        game.game_rounds = tournament.game_rounds;
+        tournament.games_created += 1;
+        tournament.tables.push(game_key);
+        // Nobody can sign for the tournament, so skip straight to the crawl
+        game.recruit(players.len() as u8, &players)?;
+        game.find_greediest();
+        Ok(())
+    }
+
+    /// Advance the top finishers of every table once the round's games have ended.
+    /// Expects the round's table games as remaining accounts, in table order.
+    pub fn advance_tournament(ctx: Context<AdvanceTournament>) -> Result<()> {
+        let tournament = &mut ctx.accounts.tournament;
+        // Check tournament phase
+        if tournament.phase != Tournament::TOURNAMENT_PHASE_IN_PROGRESS {
+            return err!(LootGoblinError::WrongTournamentPhase);
+        }
+        if tournament.tables.len() != tournament.num_tables()
+            || ctx.remaining_accounts.len() != tournament.tables.len()
+        {
+            return err!(LootGoblinError::MissingExpectedAccount);
+        }
+        let final_round = tournament.tables.len() == 1;
+        let mut advancing = Vec::new();
+        for (table, account) in tournament.tables.iter().zip(ctx.remaining_accounts) {
+            if account.key() != *table {
+                return err!(LootGoblinError::InvalidTable);
+            }
+            let loader = AccountLoader::<Game>::try_from(account)?;
+            let game = loader.load()?;
+            if game.game_phase != Game::GAME_PHASE_CRAWL_ENDED {
+                return err!(LootGoblinError::GameNotEnded);
+            }
+            let finishers = game
+                .standings()
+                .into_iter()
+                .map(|i| game.goblins[i].player)
+                .filter(|player| *player != Pubkey::default());
+            if final_round {
+                advancing.extend(finishers.take(Tournament::PRIZE_SHARES.len()));
+            } else {
+                advancing.extend(finishers.take(tournament.advance_per_table as usize));
+            }
+        }
+        tournament.tables.clear();
+        if final_round {
+            tournament.winners = advancing;
+            tournament.phase = Tournament::TOURNAMENT_PHASE_FINISHED;
+            return Ok(());
+        }
+        tournament.players = advancing;
+        tournament.round += 1;
+        Ok(())
+    }
+
+    /// Pay the prize pool out to the tournament winners.
+    /// Expects the winners' wallets as remaining accounts, best first.
+    pub fn pay_out_tournament(ctx: Context<PayOutTournament>) -> Result<()> {
+        let tournament = &mut ctx.accounts.tournament;
+        // Check tournament phase
+        if tournament.phase != Tournament::TOURNAMENT_PHASE_FINISHED {
+            return err!(LootGoblinError::WrongTournamentPhase);
+        }
+        if ctx.remaining_accounts.len() != tournament.winners.len() {
+            return err!(LootGoblinError::MissingExpectedAccount);
+        }
+        // Everything above rent exemption is prize money
+        let tournament_info = tournament.to_account_info();
+        let rent = Rent::get()?.minimum_balance(tournament_info.data_len());
+        let prize_pool = tournament_info.lamports().saturating_sub(rent);
+        let prizes = tournament.prizes(prize_pool);
+        for ((winner, account), prize) in tournament
+            .winners
+            .iter()
+            .zip(ctx.remaining_accounts)
+            .zip(prizes)
+        {
+            if account.key() != *winner {
+                return err!(LootGoblinError::InvalidPrizeRecipient);
+            }
+            **tournament_info.try_borrow_mut_lamports()? -= prize;
+            **account.try_borrow_mut_lamports()? += prize;
+        }
+        tournament.phase = Tournament::TOURNAMENT_PHASE_PAID_OUT;
+        Ok(())
+    }
+
+    /// Start a [Season] running from `starts_at` to `ends_at`, funding its reward pool.
+    pub fn create_season(
+        ctx: Context<CreateSeason>,
+        season_id: u8,
+        starts_at: i64,
+        ends_at: i64,
+        ranked_by: u8,
+        reward_pool: u64,
+    ) -> Result<()> {
+        if ends_at <= starts_at || ranked_by > Season::RANKED_BY_WINS {
+            return err!(LootGoblinError::InvalidSeasonSetting);
+        }
+        let season = &mut ctx.accounts.season;
+        season.creator = ctx.accounts.creator.key();
+        season.bump = *ctx.bumps.get("season").unwrap();
+        season.id = season_id;
+        season.starts_at = starts_at;
+        season.ends_at = ends_at;
+        season.ranked_by = ranked_by;
+        // Fund the reward pool
+        if reward_pool > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.creator.to_account_info(),
+                        to: ctx.accounts.season.to_account_info(),
+                    },
+                ),
+                reward_pool,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Enter a [Game] into a [Season] before its crawl starts, so it's recorded there
+    /// once it ends.
+    pub fn enter_season(ctx: Context<EnterSeason>) -> Result<()> {
+        let season = &ctx.accounts.season;
+        let mut game = ctx.accounts.game.load_mut()?;
+        // Check if signer is game creator
+        if ctx.accounts.creator.key() != game.creator {
+            return err!(LootGoblinError::InvalidAuthority);
+        }
+        // Check game phase
+        if game.game_phase >= Game::GAME_PHASE_CRAWL_STARTED {
+            return err!(LootGoblinError::WrongGamePhase);
+        }
+        if game.season != Pubkey::default() {
+            return err!(LootGoblinError::GameAlreadyInSeason);
+        }
+        if season.closed != 0 {
+            return err!(LootGoblinError::SeasonClosed);
+        }
+        if Clock::get()?.unix_timestamp > season.ends_at {
+            return err!(LootGoblinError::OutsideSeason);
+        }
+        game.season = season.key();
+        Ok(())
+    }
+
+    /// Add the final standings of a [Game] that ended during the [Season] it was entered
+    /// into to the season's leaderboard. Anyone can settle a game once it has ended.
+    pub fn record_season_game(ctx: Context<RecordSeasonGame>) -> Result<()> {
+        let season = &mut ctx.accounts.season;
+        let mut game = ctx.accounts.game.load_mut()?;
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_CRAWL_ENDED {
+            return err!(LootGoblinError::GameNotEnded);
+        }
+        if game.season != season.key() {
+            return err!(LootGoblinError::GameNotInSeason);
+        }
+        if game.season_recorded_at != 0 {
+            return err!(LootGoblinError::GameAlreadyRecorded);
+        }
+        if season.closed != 0 {
+            return err!(LootGoblinError::SeasonClosed);
+        }
+        if !season.is_during(game.ended_at) {
+            return err!(LootGoblinError::OutsideSeason);
+        }
+        let winner = game.standings()[0];
+        for (i, goblin) in game.goblins[..game.num_goblins as usize].iter().enumerate() {
+            if !goblin.is_cpu() {
+                season.record(goblin.player, goblin.score(), i == winner);
+            }
+        }
+        season.games_recorded += 1;
+        game.season_recorded_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Pay the reward pool out to the top of the leaderboard once the [Season] is over,
+    /// handing anything left back to the creator.
+    /// Expects the rewarded players' wallets as remaining accounts, best first.
+    pub fn close_season(ctx: Context<CloseSeason>) -> Result<()> {
+        let season = &mut ctx.accounts.season;
+        if season.closed != 0 {
+            return err!(LootGoblinError::SeasonClosed);
+        }
+        if Clock::get()?.unix_timestamp <= season.ends_at {
+            return err!(LootGoblinError::SeasonNotOver);
+        }
+        let rewarded = season.rewarded();
+        if ctx.remaining_accounts.len() != rewarded.len() {
+            return err!(LootGoblinError::MissingExpectedAccount);
+        }
+        // Everything above rent exemption is reward money
+        let season_info = season.to_account_info();
+        let rent = Rent::get()?.minimum_balance(season_info.data_len());
+        let reward_pool = season_info.lamports().saturating_sub(rent);
+        let rewards = season.rewards(reward_pool);
+        for ((player, account), reward) in rewarded.iter().zip(ctx.remaining_accounts).zip(rewards)
+        {
+            if account.key() != *player {
+                return err!(LootGoblinError::InvalidPrizeRecipient);
+            }
+            **season_info.try_borrow_mut_lamports()? -= reward;
+            **account.try_borrow_mut_lamports()? += reward;
+        }
+        let leftover = season_info.lamports().saturating_sub(rent);
+        **season_info.try_borrow_mut_lamports()? -= leftover;
+        **ctx.accounts.creator.try_borrow_mut_lamports()? += leftover;
+        season.closed = 1;
+        Ok(())
+    }
+
+    /// Open a SOL [SideBetMarket] on a game's winner that locks at `lock_turn`.
+    pub fn create_side_bet_market(ctx: Context<CreateSideBetMarket>, lock_turn: u8) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        market.bump = *ctx.bumps.get("market").unwrap();
+        market.game = ctx.accounts.game.key();
+        market.series_game = ctx.accounts.game.load()?.series_game;
+        market.lock_turn = lock_turn;
+        market.opened_at = Clock::get()?.unix_timestamp;
+        ctx.accounts
+            .game
+            .load_mut()?
+            .open_market(ctx.accounts.creator.key())
+    }
+
+    /// Open a [SideBetMarket] staked in an SPL token, escrowed in a market-owned vault.
+    pub fn create_token_side_bet_market(
+        ctx: Context<CreateTokenSideBetMarket>,
+        lock_turn: u8,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        market.bump = *ctx.bumps.get("market").unwrap();
+        market.game = ctx.accounts.game.key();
+        market.series_game = ctx.accounts.game.load()?.series_game;
+        market.mint = ctx.accounts.mint.key();
+        market.lock_turn = lock_turn;
+        market.opened_at = Clock::get()?.unix_timestamp;
+        ctx.accounts
+            .game
+            .load_mut()?
+            .open_market(ctx.accounts.creator.key())
+    }
+
+    /// Stake on the goblin at `goblin_index` ending the game with the most loot.
+    pub fn place_side_bet(ctx: Context<PlaceSideBet>, goblin_index: u8, amount: u64) -> Result<()> {
+        let game = ctx.accounts.game.load()?;
+        let market = &mut ctx.accounts.market;
+        // Check that betting is still open
+        if !market.is_open(&game) {
+            return err!(LootGoblinError::BettingClosed);
+        }
+        // Goblins aren't known until they're recruited
+        let max_goblins = match game.game_phase {
+            Game::GAME_PHASE_RECRUIT_GOBLINS => Game::MAX_PLAYERS,
+            _ => game.num_goblins as usize,
+        };
+        if goblin_index as usize >= max_goblins {
+            return err!(LootGoblinError::InvalidGoblinIndex);
+        }
+        if amount == 0 {
+            return err!(LootGoblinError::InvalidBetAmount);
+        }
+        market.pools[goblin_index as usize] += amount;
+        let side_bet = &mut ctx.accounts.side_bet;
+        side_bet.bump = *ctx.bumps.get("side_bet").unwrap();
+        side_bet.market = market.key();
+        side_bet.bettor = ctx.accounts.bettor.key();
+        side_bet.goblin_index = goblin_index;
+        side_bet.amount = amount;
+        // Escrow the stake
+        if market.is_sol() {
+            return system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.bettor.to_account_info(),
+                        to: market.to_account_info(),
+                    },
+                ),
+                amount,
+            );
+        }
+        let (Some(bettor_tokens), Some(vault), Some(token_program)) = (
+            &ctx.accounts.bettor_tokens,
+            &ctx.accounts.vault,
+            &ctx.accounts.token_program,
+        ) else {
+            return err!(LootGoblinError::MissingExpectedAccount);
+        };
+        token::transfer(
+            CpiContext::new(
+                token_program.to_account_info(),
+                token::Transfer {
+                    from: bettor_tokens.to_account_info(),
+                    to: vault.to_account_info(),
+                    authority: ctx.accounts.bettor.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    /// Record the winning goblin once the game has ended.
+    pub fn settle_side_bet_market(ctx: Context<SettleSideBetMarket>) -> Result<()> {
+        let mut game = ctx.accounts.game.load_mut()?;
+        let market = &mut ctx.accounts.market;
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_CRAWL_ENDED {
+            return err!(LootGoblinError::GameNotEnded);
+        }
+        if market.settled != 0 {
+            return err!(LootGoblinError::BetsAlreadySettled);
+        }
+        market.winning_goblin = game.standings()[0] as u8;
+        market.num_goblins = game.num_goblins;
+        market.settled = 1;
+        game.open_markets -= 1;
+        Ok(())
+    }
+
+    /// Refund every bet on a game that was closed, or that still hasn't ended long after
+    /// its market opened.
+    pub fn cancel_side_bet_market(ctx: Context<CancelSideBetMarket>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        if market.settled != 0 {
+            return err!(LootGoblinError::BetsAlreadySettled);
+        }
+        // A closed game can never be settled
+        let game_info = ctx.accounts.game.to_account_info();
+        if !game_info.data_is_empty() {
+            let game: AccountLoader<Game> = AccountLoader::try_from(&game_info)?;
+            let mut game = game.load_mut()?;
+            if game.game_phase == Game::GAME_PHASE_CRAWL_ENDED
+                || !market.is_abandoned(Clock::get()?.unix_timestamp)
+            {
+                return err!(LootGoblinError::GameNotAbandoned);
+            }
+            game.open_markets -= 1;
+        }
+        market.settled = 1;
+        market.cancelled = 1;
+        Ok(())
+    }
+
+    /// Pay out a settled bet and close it. Losing bets are closed without a payout.
+    pub fn claim_side_bet(ctx: Context<ClaimSideBet>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        if market.settled == 0 {
+            return err!(LootGoblinError::BetsNotSettled);
+        }
+        let payout = market.payout(&ctx.accounts.side_bet);
+        if payout == 0 {
+            return Ok(());
+        }
+        if market.is_sol() {
+            **market.to_account_info().try_borrow_mut_lamports()? -= payout;
+            **ctx.accounts.bettor.try_borrow_mut_lamports()? += payout;
+            return Ok(());
+        }
+        let (Some(bettor_tokens), Some(vault), Some(token_program)) = (
+            &ctx.accounts.bettor_tokens,
+            &ctx.accounts.vault,
+            &ctx.accounts.token_program,
+        ) else {
+            return err!(LootGoblinError::MissingExpectedAccount);
+        };
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            seeds::SIDE_BET_MARKET,
+            market.game.as_ref(),
+            &[market.series_game],
+            &[market.bump],
+        ]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                token::Transfer {
+                    from: vault.to_account_info(),
+                    to: bettor_tokens.to_account_info(),
+                    authority: market.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            payout,
+        )
+    }
+
+    /// Mint one of a goblin's most valuable pieces of loot as an NFT once the game has ended.
+    /// Each piece can only be claimed once since its mint address is derived from its slot
+    /// and the game of the series it was found in.
+    pub fn claim_loot_nft(
+        ctx: Context<ClaimLootNft>,
+        goblin_index: u8,
+        loot_index: u8,
+    ) -> Result<()> {
+        let mut game = ctx.accounts.game.load_mut()?;
+        // Check game phase
+        if game.game_phase != Game::GAME_PHASE_CRAWL_ENDED {
+            return err!(LootGoblinError::GameNotEnded);
+        }
+        if goblin_index >= game.num_goblins {
+            return err!(LootGoblinError::InvalidGoblinIndex);
+        }
+        // Check that the signer is the goblin's player
+        let goblin = &game.goblins[goblin_index as usize];
+        if goblin.player != ctx.accounts.signer.key() {
+            return err!(LootGoblinError::InvalidAuthority);
+        }
+        if !goblin.is_claimable_loot(loot_index as usize) {
+            return err!(LootGoblinError::LootNotClaimable);
+        }
+        let loot = goblin.loot_bag[loot_index as usize];
+        let place = 1 + game
+            .standings()
+            .iter()
+            .position(|i| *i == goblin_index as usize)
+            .unwrap() as u8;
+        game.claimed_loot[goblin_index as usize] += 1;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            seeds::LOOT_MINT_AUTHORITY,
+            &[*ctx.bumps.get("mint_authority").unwrap()],
+        ]];
+        // Mint the one and only token
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::MintTo {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.token_account.to_account_info(),
+                    authority: ctx.accounts.mint_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )?;
+        // Describe the loot
+        metadata::create_metadata_accounts_v3(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_metadata_program.to_account_info(),
+                metadata::CreateMetadataAccountsV3 {
+                    metadata: ctx.accounts.metadata.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    mint_authority: ctx.accounts.mint_authority.to_account_info(),
+                    payer: ctx.accounts.signer.to_account_info(),
+                    update_authority: ctx.accounts.mint_authority.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            DataV2 {
+                name: loot.name(),
+                symbol: Loot::NFT_SYMBOL.to_string(),
+                uri: loot.nft_uri(game.game_id(), place),
+                seller_fee_basis_points: 0,
+                creators: None,
+                collection: None,
+                uses: None,
+            },
+            false,
+            true,
+            None,
+        )?;
+        // Fix the supply at one
+        token::set_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::SetAuthority {
+                    current_authority: ctx.accounts.mint_authority.to_account_info(),
+                    account_or_mint: ctx.accounts.mint.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            AuthorityType::MintTokens,
+            None,
+        )
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(game_id: u8, game_rounds: u8)]
+pub struct CreateGame<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        init,
+        payer = creator,
+        space = Game::SIZE,
+        seeds = [seeds::GAME, creator.key().as_ref(), &[game_id]],
+        bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateGameCounter<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        init,
+        payer = creator,
+        space = GameCounter::SIZE,
+        seeds = [seeds::GAME_COUNTER, creator.key().as_ref()],
+        bump,
+    )]
+    pub game_counter: Account<'info, GameCounter>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(game_rounds: u8)]
+pub struct CreateNextGame<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [seeds::GAME_COUNTER, creator.key().as_ref()],
+        bump = game_counter.bump,
+    )]
+    pub game_counter: Account<'info, GameCounter>,
+    #[account(
+        init,
+        payer = creator,
+        space = Game::SIZE,
+        seeds = [
+            seeds::GAME,
+            creator.key().as_ref(),
+            &game_counter.next_id.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        mut,
+        seeds = [seeds::GAME_REGISTRY],
+        bump = game_registry.bump,
+    )]
+    pub game_registry: Option<Account<'info, GameRegistry>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateGameRegistry<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = GameRegistry::SIZE,
+        seeds = [seeds::GAME_REGISTRY],
+        bump,
+    )]
+    pub game_registry: Account<'info, GameRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnlistGame<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        mut,
+        seeds = [seeds::GAME_REGISTRY],
+        bump = game_registry.bump,
+    )]
+    pub game_registry: Account<'info, GameRegistry>,
+}
+
+#[derive(Accounts)]
+pub struct CloseGame<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        mut,
+        close = creator,
+        seeds = [
+            seeds::GAME,
+            creator.key().as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        mut,
+        seeds = [seeds::GAME_REGISTRY],
+        bump = game_registry.bump,
+    )]
+    pub game_registry: Account<'info, GameRegistry>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateGame<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Checked to be a game in the original layout, which can't be loaded as a [Game]
+    #[account(mut, owner = crate::ID)]
+    pub game: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(num_goblins: u8, players: Vec<Pubkey>)]
+pub struct RecruitGoblins<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            creator.key().as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        mut,
+        seeds = [seeds::GAME_REGISTRY],
+        bump = game_registry.bump,
+    )]
+    pub game_registry: Option<Account<'info, GameRegistry>>,
+}
+
+#[derive(Accounts)]
+#[instruction(goblin_index: u8, class: u8)]
+pub struct ChooseClass<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+}
+
+#[derive(Accounts)]
+pub struct FindGreediestGoblin<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            creator.key().as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+}
+
+#[derive(Accounts)]
+pub struct Rematch<'info> {
+    pub creator: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            creator.key().as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+}
+
+#[derive(Accounts)]
+#[instruction(room: u8)]
+pub struct MoveGoblin<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+pub struct RummageThroughLootSack<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+#[instruction(did_bribe: bool, hero_index: u32, loot_index: u32)]
+pub struct BribeHero<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+#[instruction(shop_index: u8, loot_indices: Vec<u8>)]
+pub struct BuyItem<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+#[instruction(item_slot: Option<u8>)]
+pub struct UseItem<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+#[instruction(item_slot: u8)]
+pub struct EquipItem<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+#[instruction(item_slot: u8)]
+pub struct DropItems<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+pub struct UseClassAbility<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+pub struct TriggerEvent<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+pub struct DetermineOutcome<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+#[instruction(target: u8)]
+pub struct ChooseTarget<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+#[instruction(choice: u8)]
+pub struct MakeAftermathDecision<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+pub struct SlapFight<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+pub struct OpenWageredSlapFight<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+    #[account(
+        init,
+        payer = signer,
+        space = WageredSlapFight::SIZE,
+        seeds = [seeds::WAGERED_SLAP_FIGHT, game.key().as_ref()],
+        bump,
+    )]
+    pub slap_fight: Account<'info, WageredSlapFight>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(goblin_index: u8)]
+pub struct CommitSlapWager<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+    #[account(
+        mut,
+        seeds = [seeds::WAGERED_SLAP_FIGHT, game.key().as_ref()],
+        bump = slap_fight.bump,
+    )]
+    pub slap_fight: Account<'info, WageredSlapFight>,
+}
+
+#[derive(Accounts)]
+#[instruction(goblin_index: u8)]
+pub struct RevealSlapWager<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+    #[account(
+        mut,
+        seeds = [seeds::WAGERED_SLAP_FIGHT, game.key().as_ref()],
+        bump = slap_fight.bump,
+    )]
+    pub slap_fight: Account<'info, WageredSlapFight>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveWageredSlapFight<'info> {
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        mut,
+        close = opener,
+        seeds = [seeds::WAGERED_SLAP_FIGHT, game.key().as_ref()],
+        bump = slap_fight.bump,
+        has_one = opener,
+    )]
+    pub slap_fight: Account<'info, WageredSlapFight>,
+    /// CHECK: refunded the fight's rent, checked against `slap_fight.opener`
+    #[account(mut)]
+    pub opener: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(plan: TurnPlan)]
+pub struct TakeTurn<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+#[instruction(goblin_index: u8, offer: TradeOffer)]
+pub struct OfferTrade<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+#[instruction(goblin_index: u8)]
+pub struct AcceptTrade<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+#[instruction(goblin_index: u8)]
+pub struct CancelTrade<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            session.player.as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+}
+
+#[derive(Accounts)]
+pub struct CreatePlayer<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = Player::SIZE,
+        seeds = [seeds::PLAYER, authority.key().as_ref()],
+        bump,
+    )]
+    pub player: Account<'info, Player>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleRatings<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+}
+
+#[derive(Accounts)]
+pub struct RecordAchievements<'info> {
+    #[account(
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        mut,
+        seeds = [seeds::PLAYER, player.authority.as_ref()],
+        bump = player.bump,
+    )]
+    pub player: Account<'info, Player>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMatchQueue<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = MatchQueue::SIZE,
+        seeds = [seeds::MATCH_QUEUE],
+        bump,
+    )]
+    pub match_queue: Account<'info, MatchQueue>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct JoinMatchQueue<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [seeds::PLAYER, authority.key().as_ref()],
+        bump = player.bump,
+    )]
+    pub player: Account<'info, Player>,
+    #[account(
+        mut,
+        seeds = [seeds::MATCH_QUEUE],
+        bump = match_queue.bump,
+    )]
+    pub match_queue: Account<'info, MatchQueue>,
+}
+
+#[derive(Accounts)]
+pub struct LeaveMatchQueue<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [seeds::MATCH_QUEUE],
+        bump = match_queue.bump,
+    )]
+    pub match_queue: Account<'info, MatchQueue>,
+}
+
+#[derive(Accounts)]
+#[instruction(num_players: u8, game_rounds: u8)]
+pub struct MatchPlayers<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [seeds::MATCH_QUEUE],
+        bump = match_queue.bump,
+    )]
+    pub match_queue: Account<'info, MatchQueue>,
+    #[account(
+        init,
+        payer = payer,
+        space = Game::SIZE,
+        seeds = [
             seeds::GAME,
-            creator.key().as_ref(),
-            &[game.load()?.id],
+            match_queue.key().as_ref(),
+            &match_queue.games_created.to_le_bytes(),
         ],
-        bump = game.load()?.bump,
+        bump,
     )]
     pub game: AccountLoader<'info, Game>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct RummageThroughLootSack<'info> {
+#[instruction(tournament_id: u8)]
+pub struct CreateTournament<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        init,
+        payer = creator,
+        space = Tournament::SIZE,
+        seeds = [seeds::TOURNAMENT, creator.key().as_ref(), &[tournament_id]],
+        bump,
+    )]
+    pub tournament: Account<'info, Tournament>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct JoinTournament<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::TOURNAMENT,
+            tournament.creator.as_ref(),
+            &[tournament.id],
+        ],
+        bump = tournament.bump,
+    )]
+    pub tournament: Account<'info, Tournament>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StartTournament<'info> {
+    pub creator: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::TOURNAMENT,
+            creator.key().as_ref(),
+            &[tournament.id],
+        ],
+        bump = tournament.bump,
+    )]
+    pub tournament: Account<'info, Tournament>,
+}
+
+#[derive(Accounts)]
+pub struct CreateTournamentTable<'info> {
     #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::TOURNAMENT,
+            creator.key().as_ref(),
+            &[tournament.id],
+        ],
+        bump = tournament.bump,
+    )]
+    pub tournament: Account<'info, Tournament>,
+    #[account(
+        init,
+        payer = creator,
+        space = Game::SIZE,
+        seeds = [
+            seeds::GAME,
+            tournament.key().as_ref(),
+            &tournament.games_created.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdvanceTournament<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::TOURNAMENT,
+            tournament.creator.as_ref(),
+            &[tournament.id],
+        ],
+        bump = tournament.bump,
+    )]
+    pub tournament: Account<'info, Tournament>,
+}
+
+#[derive(Accounts)]
+pub struct PayOutTournament<'info> {
     pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::TOURNAMENT,
+            tournament.creator.as_ref(),
+            &[tournament.id],
+        ],
+        bump = tournament.bump,
+    )]
+    pub tournament: Account<'info, Tournament>,
+}
+
+#[derive(Accounts)]
+#[instruction(season_id: u8)]
+pub struct CreateSeason<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        init,
+        payer = creator,
+        space = Season::SIZE,
+        seeds = [seeds::SEASON, creator.key().as_ref(), &[season_id]],
+        bump,
+    )]
+    pub season: Account<'info, Season>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EnterSeason<'info> {
+    pub creator: Signer<'info>,
+    #[account(
+        seeds = [
+            seeds::SEASON,
+            season.creator.as_ref(),
+            &[season.id],
+        ],
+        bump = season.bump,
+    )]
+    pub season: Account<'info, Season>,
     #[account(
         mut,
         seeds = [
             seeds::GAME,
             game.load()?.creator.as_ref(),
-            &[game.load()?.id],
+            &game.load()?.id_seed(),
         ],
         bump = game.load()?.bump,
     )]
@@ -540,16 +2651,24 @@ pub struct RummageThroughLootSack<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(did_bribe: bool, hero_index: u32, loot_index: u32)]
-pub struct BribeHero<'info> {
-    #[account(mut)]
+pub struct RecordSeasonGame<'info> {
     pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            seeds::SEASON,
+            season.creator.as_ref(),
+            &[season.id],
+        ],
+        bump = season.bump,
+    )]
+    pub season: Account<'info, Season>,
     #[account(
         mut,
         seeds = [
             seeds::GAME,
             game.load()?.creator.as_ref(),
-            &[game.load()?.id],
+            &game.load()?.id_seed(),
         ],
         bump = game.load()?.bump,
     )]
@@ -557,73 +2676,249 @@ pub struct BribeHero<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(use_item: bool)]
-pub struct UseItem<'info> {
+pub struct CloseSeason<'info> {
     #[account(mut)]
-    pub signer: Signer<'info>,
+    pub creator: Signer<'info>,
     #[account(
         mut,
+        seeds = [
+            seeds::SEASON,
+            creator.key().as_ref(),
+            &[season.id],
+        ],
+        bump = season.bump,
+    )]
+    pub season: Account<'info, Season>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey, expires_at: i64)]
+pub struct CreateSession<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    #[account(
         seeds = [
             seeds::GAME,
             game.load()?.creator.as_ref(),
-            &[game.load()?.id],
+            &game.load()?.id_seed(),
         ],
         bump = game.load()?.bump,
     )]
     pub game: AccountLoader<'info, Game>,
+    #[account(
+        init,
+        payer = player,
+        space = Session::SIZE,
+        seeds = [
+            seeds::SESSION,
+            game.key().as_ref(),
+            player.key().as_ref(),
+            &game.load()?.created_slot.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub session: Account<'info, Session>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct TriggerEvent<'info> {
+pub struct RevokeSession<'info> {
     #[account(mut)]
-    pub signer: Signer<'info>,
+    pub player: Signer<'info>,
+    #[account(
+        mut,
+        close = player,
+        seeds = [
+            seeds::SESSION,
+            session.game.as_ref(),
+            player.key().as_ref(),
+            &session.game_created_slot.to_le_bytes(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Account<'info, Session>,
+}
+
+#[derive(Accounts)]
+#[instruction(lock_turn: u8)]
+pub struct CreateSideBetMarket<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
     #[account(
         mut,
         seeds = [
             seeds::GAME,
             game.load()?.creator.as_ref(),
-            &[game.load()?.id],
+            &game.load()?.id_seed(),
         ],
         bump = game.load()?.bump,
     )]
     pub game: AccountLoader<'info, Game>,
+    #[account(
+        init,
+        payer = creator,
+        space = SideBetMarket::SIZE,
+        seeds = [seeds::SIDE_BET_MARKET, game.key().as_ref(), &[game.load()?.series_game]],
+        bump,
+    )]
+    pub market: Account<'info, SideBetMarket>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct DetermineOutcome<'info> {
+#[instruction(lock_turn: u8)]
+pub struct CreateTokenSideBetMarket<'info> {
     #[account(mut)]
-    pub signer: Signer<'info>,
+    pub creator: Signer<'info>,
     #[account(
         mut,
         seeds = [
             seeds::GAME,
             game.load()?.creator.as_ref(),
-            &[game.load()?.id],
+            &game.load()?.id_seed(),
         ],
         bump = game.load()?.bump,
     )]
     pub game: AccountLoader<'info, Game>,
+    #[account(
+        init,
+        payer = creator,
+        space = SideBetMarket::SIZE,
+        seeds = [seeds::SIDE_BET_MARKET, game.key().as_ref(), &[game.load()?.series_game]],
+        bump,
+    )]
+    pub market: Account<'info, SideBetMarket>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = creator,
+        token::mint = mint,
+        token::authority = market,
+        seeds = [seeds::SIDE_BET_VAULT, market.key().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-#[instruction(choice: u8)]
-pub struct MakeAftermathDecision<'info> {
+#[instruction(goblin_index: u8, amount: u64)]
+pub struct PlaceSideBet<'info> {
     #[account(mut)]
+    pub bettor: Signer<'info>,
+    #[account(
+        seeds = [
+            seeds::GAME,
+            game.load()?.creator.as_ref(),
+            &game.load()?.id_seed(),
+        ],
+        bump = game.load()?.bump,
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(
+        mut,
+        seeds = [seeds::SIDE_BET_MARKET, game.key().as_ref(), &[game.load()?.series_game]],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, SideBetMarket>,
+    #[account(
+        init,
+        payer = bettor,
+        space = SideBet::SIZE,
+        seeds = [seeds::SIDE_BET, market.key().as_ref(), bettor.key().as_ref()],
+        bump,
+    )]
+    pub side_bet: Account<'info, SideBet>,
+    #[account(
+        mut,
+        token::mint = market.mint,
+        token::authority = bettor,
+    )]
+    pub bettor_tokens: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [seeds::SIDE_BET_VAULT, market.key().as_ref()],
+        bump,
+    )]
+    pub vault: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleSideBetMarket<'info> {
     pub signer: Signer<'info>,
     #[account(
         mut,
         seeds = [
             seeds::GAME,
             game.load()?.creator.as_ref(),
-            &[game.load()?.id],
+            &game.load()?.id_seed(),
         ],
         bump = game.load()?.bump,
     )]
     pub game: AccountLoader<'info, Game>,
+    #[account(
+        mut,
+        seeds = [seeds::SIDE_BET_MARKET, game.key().as_ref(), &[game.load()?.series_game]],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, SideBetMarket>,
 }
 
 #[derive(Accounts)]
-pub struct SlapFight<'info> {
+pub struct CancelSideBetMarket<'info> {
+    pub signer: Signer<'info>,
+    /// CHECK: The market's game, which may have been closed
+    #[account(mut, address = market.game)]
+    pub game: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [seeds::SIDE_BET_MARKET, market.game.as_ref(), &[market.series_game]],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, SideBetMarket>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimSideBet<'info> {
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [seeds::SIDE_BET_MARKET, market.game.as_ref(), &[market.series_game]],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, SideBetMarket>,
+    #[account(
+        mut,
+        close = bettor,
+        has_one = market,
+        has_one = bettor,
+        seeds = [seeds::SIDE_BET, market.key().as_ref(), bettor.key().as_ref()],
+        bump = side_bet.bump,
+    )]
+    pub side_bet: Account<'info, SideBet>,
+    #[account(
+        mut,
+        token::mint = market.mint,
+        token::authority = bettor,
+    )]
+    pub bettor_tokens: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [seeds::SIDE_BET_VAULT, market.key().as_ref()],
+        bump,
+    )]
+    pub vault: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+#[derive(Accounts)]
+#[instruction(goblin_index: u8, loot_index: u8)]
+pub struct ClaimLootNft<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
     #[account(
@@ -631,9 +2926,47 @@ pub struct SlapFight<'info> {
         seeds = [
             seeds::GAME,
             game.load()?.creator.as_ref(),
-            &[game.load()?.id],
+            &game.load()?.id_seed(),
         ],
         bump = game.load()?.bump,
     )]
     pub game: AccountLoader<'info, Game>,
+    /// CHECK: Only signs as the mint and update authority of loot NFTs
+    #[account(seeds = [seeds::LOOT_MINT_AUTHORITY], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = signer,
+        mint::decimals = 0,
+        mint::authority = mint_authority,
+        seeds = [
+            seeds::LOOT_MINT,
+            game.key().as_ref(),
+            &[game.load()?.series_game],
+            &[goblin_index],
+            &[loot_index],
+        ],
+        bump,
+    )]
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = signer,
+        associated_token::mint = mint,
+        associated_token::authority = signer,
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+    /// CHECK: Initialized by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
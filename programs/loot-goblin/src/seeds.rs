@@ -1 +1,14 @@
 pub const GAME: &[u8] = b"game";
+pub const GAME_COUNTER: &[u8] = b"game_counter";
+pub const GAME_REGISTRY: &[u8] = b"game_registry";
+pub const PLAYER: &[u8] = b"player";
+pub const MATCH_QUEUE: &[u8] = b"match_queue";
+pub const TOURNAMENT: &[u8] = b"tournament";
+pub const SESSION: &[u8] = b"session";
+pub const SIDE_BET_MARKET: &[u8] = b"side_bet_market";
+pub const SIDE_BET_VAULT: &[u8] = b"side_bet_vault";
+pub const SIDE_BET: &[u8] = b"side_bet";
+pub const LOOT_MINT: &[u8] = b"loot_mint";
+pub const LOOT_MINT_AUTHORITY: &[u8] = b"loot_mint_authority";
+pub const WAGERED_SLAP_FIGHT: &[u8] = b"wagered_slap_fight";
+pub const SEASON: &[u8] = b"season";
@@ -0,0 +1,20 @@
+/// Feats a goblin can pull off during a game, recorded on its player's profile
+/// afterwards.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Achievement {
+    RobbedEveryone = 0, // stole from every opponent in one game
+    LastGaspSlap,       // won a slap fight at 1 health
+    BagFull,            // finished with a full loot bag
+}
+impl Achievement {
+    pub const LEN: usize = Achievement::BagFull as usize + 1;
+    pub const ALL: [Achievement; Achievement::LEN] = [
+        Achievement::RobbedEveryone,
+        Achievement::LastGaspSlap,
+        Achievement::BagFull,
+    ];
+    pub fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
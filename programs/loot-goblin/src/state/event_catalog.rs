@@ -0,0 +1,24 @@
+use crate::state::EventOutcome;
+
+/// One of the two choices an event offers the turn goblin.
+#[derive(Debug)]
+pub struct EventChoice {
+    pub label: &'static str,         // client text key of the choice
+    pub side_effects: &'static [u8], // Game::SIDE_EFFECT_* the choice's side-effect is drawn from
+}
+
+/// An event goblins can run into, compiled from `data/event_catalog.json` by `build.rs`.
+#[derive(Debug)]
+pub struct EventDef {
+    pub id: u8,
+    pub text: &'static str, // client text key of the event's narration
+    pub choices: [EventChoice; 2],
+    pub weight_modifiers: &'static [(EventOutcome, i8)], // added to the outcome weights
+}
+impl EventDef {
+    pub fn get(id: u8) -> Option<&'static EventDef> {
+        EVENT_CATALOG.iter().find(|event| event.id == id)
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/event_catalog.rs"));
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+use crate::state::Goblin;
+
+/// What happens to a goblin whose health hits 0. The defaults heal it back up at the
+/// start of its next turn with no other penalty.
+#[derive(
+    Debug, Copy, Clone, Default, Zeroable, Pod, AnchorSerialize, AnchorDeserialize, PartialEq, Eq,
+)]
+#[repr(C)]
+pub struct FaintRules {
+    pub skip_turn: u8,      // 1 - a fainted goblin sits out its next turn
+    pub loot_percent: u8,   // share of its loot a fainting goblin scatters to the others
+    pub max_faints: u8,     // faints before a goblin is out of the game, 0 - never
+    pub recover_health: u8, // health a fainted goblin comes back with, 0 - full health
+}
+impl FaintRules {
+    pub fn is_valid(&self) -> bool {
+        self.skip_turn <= 1 && self.loot_percent <= 100 && self.recover_health <= Goblin::MAX_HEALTH
+    }
+    pub fn is_elimination(&self) -> bool {
+        self.max_faints > 0
+    }
+    pub fn is_out(&self, faints: u8) -> bool {
+        self.is_elimination() && faints >= self.max_faints
+    }
+    pub fn recovered_health(&self) -> u8 {
+        match self.recover_health {
+            0 => Goblin::MAX_HEALTH,
+            health => health,
+        }
+    }
+    /// How many of `pieces` pieces of loot a fainting goblin drops, rounded down.
+    pub fn loot_to_scatter(&self, pieces: usize) -> usize {
+        pieces * self.loot_percent as usize / 100
+    }
+}
@@ -1,6 +1,12 @@
 use anchor_lang::prelude::*;
 use bytemuck::{Pod, Zeroable};
 
+use crate::error::LootGoblinError;
+use crate::state::{
+    side_effect, Achievement, EventDef, FaintRules, GameMode, GoblinClass, Hero, ItemStack,
+    LegacyGame, Loot, LootCategory, Room, RoomKind, Session, StatusEffect, Trade, TradeOffer,
+    WageredSlapFight, EVENT_CATALOG,
+};
 use crate::utils::Dice;
 
 #[account(zero_copy)]
@@ -8,7 +14,7 @@ use crate::utils::Dice;
 pub struct Game {
     pub bump: u8,
     pub creator: Pubkey,
-    pub id: u8,
+    pub id: u8, // legacy games only, see `game_id`
     // 0 - new game
     // 1 - setup goblins
     // 2 - greed roll
@@ -19,21 +25,51 @@ pub struct Game {
     pub num_goblins: u8,
     pub turn_count: u8,
     pub turn_goblin: u8,
-    pub turn_phase: u8,  // 0 - rummage, 1 - bribe, 2 - move
+    pub turn_phase: u8,  // TURN_PHASE_*, goblins can move during the rummage phase
     pub turn_events: u8, // number of events this turn
     pub rummage_success_min: u8,
     pub event: u8,                   // 0 - none, 1+ - things that happen
     pub event_side_effects: [u8; 2], // events give the turn goblin 2 choices with possible side-effects
     pub event_outcome: u8,           // 0 - none, 1+ EventOutcome
     pub aftermath_option: u8,        // 0 - choose, 1 - must continue, 2 - must stop
-    pub hero_bribe_rates: [u8; 4],   // thief, wizard, warrior (defends based on roll), merchant
+    pub hero_bribe_rates: [u8; 4],   // least loot value each hero takes as a bribe
     pub available_items: [u8; 4], // ring of reflect, healing potion, shield, cursed scroll (2x damage)
     pub goblins: [Goblin; 4],
+    pub ratings_settled: u8, // 0 - no, 1 - player ratings updated from final standings
+    pub trades: [Trade; 4],  // open trade offered by each goblin
+    pub heroes: [Hero; 4],   // thief, wizard, warrior, merchant
+    pub rooms: [Room; 8],    // dungeon map, goblins start in room 0
+    pub game_mode: u8,       // GameMode
+    pub boss_health: u8,     // co-op only, the goblins win once it reaches 0
+    pub boss_max_health: u8,
+    pub boss_defeated_at: u8, // turn_count the boss was defeated at, 0 - not defeated
+    pub team_scores: [u16; 2], // team games only, summed final scores per team
+    pub faint_rules: FaintRules,
+    pub risk_it: u8,             // 1 - the turn goblin risked it on its latest event
+    pub turn_risks: u8,          // events the turn goblin has risked it on this turn
+    pub risky_plays: u16,        // events risked on by every goblin this game
+    pub ended_at: i64,           // unix timestamp the crawl ended at
+    pub season: Pubkey,          // season the game was entered into, default - none
+    pub series_scores: [u16; 4], // final scores summed over every game of the series
+    pub series_wins: [u8; 4],    // games of the series won by each goblin
+    pub series_game: u8,         // games played in the series before this one
+    pub best_of: u8,             // games in the series, 0 - not a series
+    pub series_winner: u8,       // 0 - undecided, 1+ goblin index + 1
+    pub first_goblin: u8,        // goblin that took the first turn
+    pub wide_id: u64,            // games seeded with SEED_SCHEME_WIDE only, see `game_id`
+    pub claimed_loot: [u8; 4],   // loot NFTs minted per goblin over the whole series
+    pub seed_scheme: u8,         // how the id is encoded in the game seeds, see `id_seed`
+    pub side_bet_markets: u8,    // side bet markets opened over the whole series
+    pub open_markets: u8,        // side bet markets that haven't settled yet
+    pub migrated: u8,            // 1 - converted from the original layout by `migrate`
+    pub season_recorded_at: i64, // unix timestamp the game was recorded in its season, 0 - not yet
+    pub created_at: i64,         // unix timestamp the game was created at, 0 - migrated
+    pub created_slot: u64,       // slot the game was created in, keeps sessions from outliving it
 }
 impl Game {
     pub const SIZE: usize = 8 + // discriminator
-        32 + // creator
         1 + // bump
+        32 + // creator
         1 + // id
         1 + // game_phase
         1 + // game_rounds
@@ -60,10 +96,96 @@ impl Game {
             1 + // greed
             1 + // last_roll
             1 + // last_roll_at
-            1 + // held_item
+            1 + // equipped
             4 + // loot_bag (len)
-            32 // loot_bag (entries)
-        );
+            32 * ( // loot_bag (entries)
+                1 + // rarity
+                1 + // category
+                1 + // id
+                1 + // found_turn
+                1 // found_outcome
+            ) +
+            4 + // inventory (len)
+            4 * ( // inventory (entries)
+                1 + // item
+                1 // count
+            ) +
+            4 + // status_effects (len)
+            StatusEffect::LEN + // status_effects (entries)
+            1 + // class
+            1 + // ability_used
+            1 + // room
+            1 + // moved_at
+            1 + // boss_damage
+            1 + // team
+            1 + // faints
+            1 + // safe_plays
+            1 + // stolen_from
+            1 // achievements
+        ) +
+        1 + // ratings_settled
+        4 + // trades (len)
+        4 * ( // trades (entries)
+            1 + // is_open
+            1 + // with_goblin
+            1 + // offered_at_turn
+            1 + // offered_item
+            1 + // requested_item
+            1 + // requested_loot_index
+            5 + // offered_loot
+            5 // requested_loot
+        ) +
+        4 + // heroes (len)
+        4 * ( // heroes (entries)
+            1 + // position
+            1 + // mood
+            1 + // bribed_by
+            1 + // refused_by
+            1 + // attacked
+            1 + // bribe_value
+            1 + // encounters
+            1 // in_event
+        ) +
+        4 + // rooms (len)
+        8 * ( // rooms (entries)
+            1 + // kind
+            1 // exits
+        ) +
+        1 + // game_mode
+        1 + // boss_health
+        1 + // boss_max_health
+        1 + // boss_defeated_at
+        4 + // team_scores (len)
+        2 * 2 + // team_scores (entries)
+        1 + // faint_rules.skip_turn
+        1 + // faint_rules.loot_percent
+        1 + // faint_rules.max_faints
+        1 + // faint_rules.recover_health
+        1 + // risk_it
+        1 + // turn_risks
+        2 + // risky_plays
+        8 + // ended_at
+        32 + // season
+        4 + // series_scores (len)
+        4 * 2 + // series_scores (entries)
+        4 + // series_wins (len)
+        4 + // series_wins (entries)
+        1 + // series_game
+        1 + // best_of
+        1 + // series_winner
+        1 + // first_goblin
+        8 + // wide_id
+        4 + // claimed_loot (len)
+        4 + // claimed_loot (entries)
+        1 + // seed_scheme
+        1 + // side_bet_markets
+        1 + // open_markets
+        1 + // migrated
+        8 + // season_recorded_at
+        8 + // created_at
+        8; // created_slot
+    pub const SEED_SCHEME_LEGACY: u8 = 0; // single byte id
+    pub const SEED_SCHEME_WIDE: u8 = 1; // 8 byte little-endian id
     pub const MIN_PLAYERS: usize = 1;
     pub const MAX_PLAYERS: usize = 4;
     pub const GAME_PHASE_NEW_GAME: u8 = 0;
@@ -78,10 +200,158 @@ impl Game {
     pub const TURN_PHASE_OUTCOME: u8 = 4; // handle outcome
     pub const TURN_PHASE_AFTERMATH: u8 = 5; // handle choice continue or stop
     pub const TURN_PHASE_SLAP_FIGHT: u8 = 6; // optional
+    pub const TURN_PHASE_CHOOSE_TARGET: u8 = 7; // choose who to steal from
+    pub const TURN_PHASE_WAGERED_SLAP_FIGHT: u8 = 8; // wagers being committed and revealed
     pub const AFTERMATH_OPTION_EITHER: u8 = 0;
     pub const AFTERMATH_OPTION_CONTINUE: u8 = 1;
     pub const AFTERMATH_OPTION_STOP: u8 = 2;
     pub const AFTERMATH_OPTION_LEN: u8 = 3;
+    pub const MAX_PLANNED_EVENTS: usize = 8; // keeps `take_turn` within the compute budget
+    /// Outcomes a hero makes likelier for goblins it holds a grudge against, ordered like `heroes`.
+    pub const HERO_GRUDGE_OUTCOMES: [EventOutcome; 4] = [
+        EventOutcome::LootGotStolen,
+        EventOutcome::LoseItem,
+        EventOutcome::GetAttacked,
+        EventOutcome::ItemGotStolen,
+    ];
+    /// Outcomes a hero makes likelier for goblins that bribed it, ordered like `heroes`.
+    pub const HERO_FAVOR_OUTCOMES: [EventOutcome; 4] = [
+        EventOutcome::StealLoot,
+        EventOutcome::BoostLuck,
+        EventOutcome::Heal,
+        EventOutcome::GetItem,
+    ];
+    pub const HERO_EVENT_WEIGHT: u16 = 8;
+    pub const ITEM_RING_OF_REFLECT: u8 = 1;
+    pub const ITEM_HEALING_POTION: u8 = 2;
+    pub const ITEM_SHIELD: u8 = 3;
+    pub const ITEM_CURSED_SCROLL: u8 = 4; // curses another goblin
+    pub const NUM_ITEMS: u8 = 4;
+    pub const SIDE_EFFECT_POISON: u8 = side_effect::POISON;
+    pub const SIDE_EFFECT_BLESSING: u8 = side_effect::BLESSING;
+    pub const SIDE_EFFECT_GREEDY_FRENZY: u8 = side_effect::GREEDY_FRENZY;
+    pub const BLESSED_WEIGHT: u16 = 5;
+    /// Outcomes each class makes likelier, indexed by class - 1.
+    pub const CLASS_FAVORED_OUTCOMES: [[EventOutcome; 2]; GoblinClass::LEN - 1] = [
+        [EventOutcome::StealLoot, EventOutcome::StealItem],
+        [EventOutcome::SlapFight, EventOutcome::GetLoot],
+        [EventOutcome::Heal, EventOutcome::BoostLuck],
+        [EventOutcome::GetLoot, EventOutcome::GetItem],
+    ];
+    /// Outcome each class halves the odds of, indexed by class - 1.
+    pub const CLASS_RESISTED_OUTCOMES: [EventOutcome; GoblinClass::LEN - 1] = [
+        EventOutcome::LootGotStolen,
+        EventOutcome::GetAttacked,
+        EventOutcome::LoseItem,
+        EventOutcome::LoseLoot,
+    ];
+    pub const CLASS_WEIGHT: u16 = 5;
+    pub const HOARDER_STASH_SIZE: usize = 2;
+    pub const EXTRA_PASSAGES: usize = 2; // passages added on top of the ones connecting every room
+    pub const ROOM_WEIGHT: u16 = 6;
+    pub const BOSS_HEALTH_PER_GOBLIN: u8 = 3;
+    pub const NUM_TEAMS: usize = 2;
+    pub const MAX_BEST_OF: u8 = 9;
+    pub const LISTING_DURATION: i64 = 60 * 60 * 24; // anyone can unlist open games after 1 day
+    pub const SAFE_OK_WEIGHT: u16 = 40; // playing it safe makes nothing happening very likely
+    pub const CPU_MAX_RISKS: u8 = 2; // CPU goblins risk it on this many events per turn
+    pub const MAX_SLAP_LUCK_BONUS: u8 = 3;
+    pub const GREED_PER_SLAP_BONUS: u8 = 4;
+    /// Loot value each item costs in the shop, indexed by item id - 1.
+    pub const ITEM_PRICES: [u16; Game::NUM_ITEMS as usize] = [2, 2, 3, 3];
+    pub fn game_id(&self) -> u64 {
+        match self.seed_scheme {
+            Game::SEED_SCHEME_LEGACY => self.id as u64,
+            _ => self.wide_id,
+        }
+    }
+    /// The id as it appears in the game's PDA seeds.
+    pub fn id_seed(&self) -> Vec<u8> {
+        match self.seed_scheme {
+            Game::SEED_SCHEME_LEGACY => vec![self.id],
+            _ => self.wide_id.to_le_bytes().to_vec(),
+        }
+    }
+    /// Convert a game created with the original account layout. Games that are already
+    /// underway get the dungeon and heroes they'd otherwise be missing.
+    pub fn migrate(&mut self, legacy: &LegacyGame) {
+        bytemuck::bytes_of_mut(self).fill(0);
+        self.bump = legacy.bump;
+        self.creator = legacy.creator;
+        self.id = legacy.id;
+        self.seed_scheme = Game::SEED_SCHEME_LEGACY;
+        self.migrated = 1;
+        self.game_phase = legacy.game_phase;
+        self.game_rounds = legacy.game_rounds;
+        self.num_goblins = legacy.num_goblins;
+        self.turn_count = legacy.turn_count;
+        self.turn_goblin = legacy.turn_goblin;
+        self.turn_phase = legacy.turn_phase;
+        self.turn_events = legacy.turn_events;
+        self.rummage_success_min = legacy.rummage_success_min;
+        self.event = legacy.event;
+        self.event_side_effects = legacy.event_side_effects;
+        self.event_outcome = legacy.event_outcome;
+        self.aftermath_option = legacy.aftermath_option;
+        self.available_items = legacy.available_items;
+        for (goblin, legacy_goblin) in self.goblins.iter_mut().zip(legacy.goblins.iter()) {
+            goblin.player = legacy_goblin.player;
+            goblin.health = legacy_goblin.health;
+            goblin.luck = legacy_goblin.luck;
+            goblin.greed = legacy_goblin.greed;
+            goblin.last_roll = legacy_goblin.last_roll;
+            goblin.last_roll_at = legacy_goblin.last_roll_at;
+            if legacy_goblin.held_item != 0 {
+                goblin.inventory[0] = ItemStack {
+                    item: legacy_goblin.held_item,
+                    count: 1,
+                };
+            }
+            for (loot, value) in goblin.loot_bag.iter_mut().zip(legacy_goblin.loot_bag) {
+                *loot = Loot::from_legacy(value);
+            }
+        }
+        if self.game_phase >= Game::GAME_PHASE_CRAWL_STARTED {
+            let mut dice = Dice::new();
+            self.generate_dungeon(&mut dice);
+            self.send_heroes(&mut dice);
+        }
+        self.hero_bribe_rates = legacy.hero_bribe_rates;
+    }
+    /// Count a side bet market opened on the game. Only the creator can open one, and
+    /// only before the game ends.
+    pub fn open_market(&mut self, opener: Pubkey) -> Result<()> {
+        if opener != self.creator {
+            return err!(LootGoblinError::InvalidAuthority);
+        }
+        if self.game_phase > Game::GAME_PHASE_CRAWL_STARTED {
+            return err!(LootGoblinError::WrongGamePhase);
+        }
+        self.side_bet_markets += 1;
+        self.open_markets += 1;
+        Ok(())
+    }
+    /// Whether accounts derived from the game's address still exist, in which case
+    /// closing it would let a new game take over that address.
+    pub fn has_linked_accounts(&self) -> bool {
+        self.side_bet_markets > 0 || self.claimed_loot.iter().any(|claimed| *claimed > 0)
+    }
+    pub fn is_open(&self) -> bool {
+        self.game_phase == Game::GAME_PHASE_RECRUIT_GOBLINS
+    }
+    pub fn is_coop(&self) -> bool {
+        self.game_mode == GameMode::Coop as u8
+    }
+    pub fn is_team_game(&self) -> bool {
+        self.game_mode == GameMode::Teams as u8
+    }
+    /// Whether two different goblins are on the same side: every goblin is in co-op games,
+    /// and goblins on the same team are in team games.
+    pub fn are_teammates(&self, a: usize, b: usize) -> bool {
+        a != b
+            && (self.is_coop()
+                || (self.is_team_game() && self.goblins[a].team == self.goblins[b].team))
+    }
     pub fn ptr(&self) -> *const Game {
         self as *const Game
     }
@@ -100,31 +370,1229 @@ impl Game {
         let goblins_ptr = self.goblins.as_ptr() as *mut Goblin;
         unsafe { &mut *goblins_ptr.add(i) }
     }
-    pub fn new_random_event(&mut self, dice: &mut Dice) {
-        self.event = 0;
-        while self.event == 0 {
-            self.event = dice.roll(Dice::MAX);
+    pub fn recruit(&mut self, num_goblins: u8, players: &[Pubkey]) -> Result<()> {
+        // Check num players
+        let num_players = players.len();
+        if num_players < Game::MIN_PLAYERS {
+            return err!(LootGoblinError::TooFewPlayers);
+        }
+        if num_players > Game::MAX_PLAYERS {
+            return err!(LootGoblinError::TooManyPlayers);
+        }
+        if num_players > num_goblins as usize {
+            return err!(LootGoblinError::TooManyPlayers);
+        }
+        if self.is_team_game() && (num_goblins as usize) < self.goblins.len() {
+            return err!(LootGoblinError::NotEnoughGoblinsForTeams);
+        }
+        self.num_goblins = num_goblins;
+        // Init goblins
+        for (i, player) in players.iter().enumerate() {
+            self.goblins[i].init(*player);
+        }
+        // Teammates sit across from each other so turns alternate between the teams
+        if self.is_team_game() {
+            for (i, goblin) in self.goblins.iter_mut().enumerate() {
+                goblin.team = (i % Game::NUM_TEAMS) as u8;
+            }
+        }
+        // Move to next phrase
+        self.game_phase = Game::GAME_PHASE_FIND_GREEDIEST;
+        Ok(())
+    }
+    pub fn find_greediest(&mut self) {
+        let mut dice = Dice::new();
+        // Goblins that didn't choose a class get a random one
+        for goblin in self.goblins[..self.num_goblins as usize].iter_mut() {
+            if goblin.class == GoblinClass::None as u8 {
+                goblin.class = 1 + dice.roll(GoblinClass::LEN as u8 - 1);
+            }
+        }
+        // Get a unique roll per goblin
+        let rolls = dice.roll_unique(Dice::D10, self.num_goblins as usize);
+        // Update goblin greed
+        let mut max_greed = 0;
+        let mut max_index = 0;
+        for (i, greed) in rolls.iter().enumerate() {
+            self.goblins[i].greed = *greed;
+            self.goblins[i].last_roll = *greed;
+            self.goblins[i].last_roll_at = self.turn_count;
+            if *greed > max_greed {
+                max_greed = *greed;
+                max_index = i;
+            }
+        }
+        self.generate_dungeon(&mut dice);
+        self.send_heroes(&mut dice);
+        // The whole party has to take on the boss
+        if self.is_coop() {
+            self.boss_max_health = Game::BOSS_HEALTH_PER_GOBLIN * self.num_goblins;
+            self.boss_health = self.boss_max_health;
+        }
+        // Let the crawl commence!
+        self.game_phase = Game::GAME_PHASE_CRAWL_STARTED;
+        // The greediest goblin goes first, then the next goblin over in every rematch
+        if self.series_game == 0 {
+            self.first_goblin = max_index as u8;
+        }
+        self.turn_goblin = self.first_goblin;
+        self.start_turn();
+    }
+    /// Send the heroes into the dungeon, starting in their lairs when there are any.
+    pub fn send_heroes(&mut self, dice: &mut Dice) {
+        let lairs: Vec<u8> = (1..self.rooms.len() as u8)
+            .filter(|i| self.rooms[*i as usize].kind == RoomKind::HeroLair as u8)
+            .collect();
+        for (hero, bribe_rate) in self.heroes.iter_mut().zip(self.hero_bribe_rates.iter_mut()) {
+            let room = if lairs.is_empty() {
+                1 + dice.roll(self.rooms.len() as u8 - 1)
+            } else {
+                lairs[dice.roll(lairs.len() as u8) as usize]
+            };
+            hero.init(room);
+            *bribe_rate = 1 + dice.roll(Dice::LOOT);
+        }
+    }
+    /// Connect every room to an earlier one so the whole dungeon can be reached, then add
+    /// a few more passages to make loops. Room 0 is the empty entrance.
+    pub fn generate_dungeon(&mut self, dice: &mut Dice) {
+        self.rooms = Default::default();
+        for i in 1..self.rooms.len() {
+            self.rooms[i].kind = dice.roll(RoomKind::LEN as u8);
+            self.connect_rooms(i, dice.roll(i as u8) as usize);
+        }
+        for _ in 0..Game::EXTRA_PASSAGES {
+            let a = dice.roll(self.rooms.len() as u8) as usize;
+            let b = dice.roll(self.rooms.len() as u8) as usize;
+            if a != b {
+                self.connect_rooms(a, b);
+            }
+        }
+    }
+    fn connect_rooms(&mut self, a: usize, b: usize) {
+        self.rooms[a].exits |= 1 << b;
+        self.rooms[b].exits |= 1 << a;
+    }
+    /// Move the turn goblin through a passage to a connected room, once per turn.
+    pub fn move_goblin(&mut self, room: u8) -> Result<()> {
+        let turn_count = self.turn_count;
+        let goblin = unsafe { (*self.ptr()).get_turn_goblin() };
+        if goblin.moved_at == turn_count {
+            return err!(LootGoblinError::AlreadyMoved);
+        }
+        if !self.rooms[goblin.room as usize].connects_to(room as usize) {
+            return err!(LootGoblinError::InvalidRoom);
         }
+        goblin.room = room;
+        goblin.moved_at = turn_count;
+        Ok(())
+    }
+    pub fn new_random_event(&mut self, dice: &mut Dice) {
+        let event = &EVENT_CATALOG[dice.roll(EVENT_CATALOG.len() as u8) as usize];
+        self.event = event.id;
         self.turn_events += 1;
-        // randomize event choice side-effects
-        let option_a = dice.roll(Dice::D10);
-        let option_b = dice.roll(Dice::D10);
-        self.event_side_effects = [option_a, option_b];
+        // Each choice's side-effect comes from its pool in the catalog
+        for (side_effect, choice) in self.event_side_effects.iter_mut().zip(&event.choices) {
+            *side_effect = choice.side_effects[dice.roll(choice.side_effects.len() as u8) as usize];
+        }
+        // A nearby hero may get caught up in the event
+        for hero in self.heroes.iter_mut() {
+            hero.in_event = 0;
+        }
+        let room = self.get_turn_goblin().room;
+        let near: Vec<usize> = (0..self.heroes.len())
+            .filter(|i| self.heroes[*i].is_in(room))
+            .collect();
+        if !near.is_empty() {
+            let hero = &mut self.heroes[near[dice.roll(near.len() as u8) as usize]];
+            hero.in_event = 1;
+            hero.encounters = hero.encounters.saturating_add(1);
+        }
+    }
+    /// Index of the hero taking part in the current event.
+    pub fn event_hero(&self) -> Option<usize> {
+        self.heroes.iter().position(|hero| hero.in_event != 0)
+    }
+    pub fn rummage(&mut self, dice: &mut Dice) {
+        let goblin = self.get_turn_goblin();
+        if dice.roll(Dice::D10) >= self.rummage_success_min {
+            goblin.add_random_loot(dice, self.turn_count, Loot::FOUND_RUMMAGING);
+        }
+        // Bribes are only offered when a hero is in the same room
+        if !self.heroes.iter().any(|hero| hero.is_in(goblin.room)) {
+            // Move to outcome phase w new event
+            self.new_random_event(dice);
+            self.turn_phase = Game::TURN_PHASE_OUTCOME;
+            return;
+        }
+        // Move to bribe phase
+        self.turn_phase = Game::TURN_PHASE_BRIBE;
+    }
+    /// Bribe the hero at `hero_index` with the loot at `loot_index`, or refuse every
+    /// nearby hero when `bribe` is none.
+    pub fn bribe_hero(&mut self, bribe: Option<(usize, usize)>, dice: &mut Dice) -> Result<()> {
+        let goblin_index = (self.turn_goblin % self.num_goblins) as usize;
+        let goblin = unsafe { (*self.ptr()).get_turn_goblin() };
+        // Check if the player is bribing a hero
+        match bribe {
+            Some((hero_index, loot_index)) => {
+                let Some(hero) = self
+                    .heroes
+                    .get_mut(hero_index)
+                    .filter(|hero| hero.is_in(goblin.room))
+                else {
+                    return err!(LootGoblinError::InvalidHero);
+                };
+                let Some(loot) = goblin
+                    .loot_bag
+                    .get_mut(loot_index)
+                    .filter(|loot| !loot.is_empty())
+                else {
+                    return err!(LootGoblinError::InvalidLootIndex);
+                };
+                if loot.value() < self.hero_bribe_rates[hero_index] as u16 {
+                    return err!(LootGoblinError::BribeTooSmall);
+                }
+                hero.take_bribe(goblin_index, std::mem::take(loot).value());
+            }
+            None => {
+                for hero in self
+                    .heroes
+                    .iter_mut()
+                    .filter(|hero| hero.is_in(goblin.room))
+                {
+                    hero.remember_refusal(goblin_index);
+                }
+            }
+        }
+        if !self.get_turn_goblin().has_items() {
+            // Move to outcome phase w new event
+            self.new_random_event(dice);
+            self.turn_phase = Game::TURN_PHASE_OUTCOME;
+            return Ok(());
+        }
+        // Move to item phase
+        self.turn_phase = Game::TURN_PHASE_ITEM;
+        Ok(())
+    }
+    /// Use one of the items in the turn goblin's inventory slot `item_slot`, if any.
+    pub fn use_item(&mut self, item_slot: Option<usize>, dice: &mut Dice) -> Result<()> {
+        if let Some(slot) = item_slot {
+            let goblin = self.get_turn_goblin();
+            if !goblin.has_item_in(slot) {
+                return err!(LootGoblinError::InvalidItemSlot);
+            }
+            match goblin.take_item(slot) {
+                Game::ITEM_RING_OF_REFLECT => {
+                    goblin.apply_status(StatusEffect::Reflecting, StatusEffect::DURATION);
+                }
+                Game::ITEM_HEALING_POTION => {
+                    goblin.health = Goblin::MAX_HEALTH;
+                    goblin.status_effects[StatusEffect::Poisoned as usize] = 0;
+                }
+                Game::ITEM_SHIELD => {
+                    goblin.apply_status(StatusEffect::Shielded, StatusEffect::DURATION);
+                }
+                Game::ITEM_CURSED_SCROLL => {
+                    if let Some(i) = self.random_opponent(dice) {
+                        self.get_goblin_mut(i)
+                            .apply_status(StatusEffect::Cursed, StatusEffect::DURATION);
+                    }
+                }
+                _ => {}
+            }
+        }
+        // Move to event phase
+        // self.turn_phase = Game::TURN_PHASE_EVENT;
+        // Move to outcome phase w new event
+        self.new_random_event(dice);
+        self.turn_phase = Game::TURN_PHASE_OUTCOME;
+        Ok(())
+    }
+    pub fn determine_outcome(
+        &mut self,
+        choice: u8,
+        risk_it: bool,
+        dice: &mut Dice,
+    ) -> Option<EventOutcome> {
+        let goblin = unsafe { (*self.ptr()).get_turn_goblin() };
+        // Record the choice to risk it or play it safe
+        self.risk_it = risk_it as u8;
+        if risk_it {
+            self.turn_risks = self.turn_risks.saturating_add(1);
+            self.risky_plays = self.risky_plays.saturating_add(1);
+        } else {
+            goblin.safe_plays = goblin.safe_plays.saturating_add(1);
+        }
+        // Handle choice side-effects
+        let i = choice as usize % self.event_side_effects.len();
+        match self.event_side_effects[i] {
+            Game::SIDE_EFFECT_POISON => {
+                goblin.apply_status(StatusEffect::Poisoned, StatusEffect::DURATION);
+            }
+            Game::SIDE_EFFECT_BLESSING => {
+                goblin.apply_status(StatusEffect::Blessed, StatusEffect::DURATION);
+            }
+            Game::SIDE_EFFECT_GREEDY_FRENZY => {
+                goblin.apply_status(StatusEffect::GreedyFrenzy, StatusEffect::DURATION);
+            }
+            _side_effect_id => {
+                // TODO
+                // ...
+            }
+        }
+        // Make sure goblin didn't faint from side-effects
+        if goblin.health == 0 {
+            // Start the next goblin's turn
+            self.advance_to_next_goblin();
+            self.start_turn();
+            return None;
+        }
+        // Calculate rich tax (richer goblins are less lucky)
+        let rich_tax = goblin.score() / 10;
+        let luck = goblin.luck as u16;
+        let greed = goblin.greed as u16;
+        // Set outcome probabilities, saturating so stacked bonuses can't overflow
+        let mut weights = [0u16; EventOutcome::LEN];
+        let add_weight = |weights: &mut [u16], outcome: EventOutcome, bonus: u16| {
+            weights[outcome as usize] = weights[outcome as usize].saturating_add(bonus);
+        };
+        // Good stuff
+        weights[EventOutcome::GetLoot as usize] = 10 + luck;
+        weights[EventOutcome::GetItem as usize] = 1 + luck;
+        weights[EventOutcome::StealLoot as usize] = 1 + luck;
+        weights[EventOutcome::StealItem as usize] = 1 + luck;
+        weights[EventOutcome::Heal as usize] = 1 + luck;
+        weights[EventOutcome::BoostLuck as usize] = 1 + greed;
+        weights[EventOutcome::ReduceGreed as usize] = 1 + greed;
+        // Bad + neutral stuff
+        weights[EventOutcome::LoseLoot as usize] = (10 + greed).saturating_add(rich_tax);
+        weights[EventOutcome::LoseItem as usize] = (1 + greed).saturating_add(rich_tax);
+        weights[EventOutcome::LootGotStolen as usize] = (1 + greed).saturating_add(rich_tax);
+        weights[EventOutcome::ItemGotStolen as usize] = (1 + greed).saturating_add(rich_tax);
+        weights[EventOutcome::SlapFight as usize] = (1 + greed).saturating_add(rich_tax);
+        weights[EventOutcome::GetAttacked as usize] =
+            (greed * self.turn_events as u16).saturating_add(1);
+        weights[EventOutcome::OK as usize] = 1 + luck;
+        // The room the goblin is in
+        match self.rooms[goblin.room as usize].kind {
+            k if k == RoomKind::Treasure as u8 => {
+                add_weight(&mut weights, EventOutcome::GetLoot, Game::ROOM_WEIGHT);
+                add_weight(&mut weights, EventOutcome::GetItem, Game::ROOM_WEIGHT);
+            }
+            k if k == RoomKind::Trap as u8 => {
+                add_weight(&mut weights, EventOutcome::GetAttacked, Game::ROOM_WEIGHT);
+                add_weight(&mut weights, EventOutcome::LoseLoot, Game::ROOM_WEIGHT);
+            }
+            k if k == RoomKind::HeroLair as u8 => {
+                add_weight(&mut weights, EventOutcome::GetAttacked, Game::ROOM_WEIGHT);
+                add_weight(&mut weights, EventOutcome::LootGotStolen, Game::ROOM_WEIGHT);
+            }
+            k if k == RoomKind::Shrine as u8 => {
+                add_weight(&mut weights, EventOutcome::Heal, Game::ROOM_WEIGHT);
+                add_weight(&mut weights, EventOutcome::BoostLuck, Game::ROOM_WEIGHT);
+                weights[EventOutcome::GetAttacked as usize] = 0;
+            }
+            _ => {}
+        }
+        // Class perks
+        if goblin.class != GoblinClass::None as u8 {
+            let c = goblin.class as usize - 1;
+            for outcome in Game::CLASS_FAVORED_OUTCOMES[c] {
+                add_weight(&mut weights, outcome, Game::CLASS_WEIGHT);
+            }
+            weights[Game::CLASS_RESISTED_OUTCOMES[c] as usize] /= 2;
+        }
+        // Status effects
+        if goblin.has_status(StatusEffect::Poisoned) {
+            weights[EventOutcome::Heal as usize] = 0;
+        }
+        if goblin.has_status(StatusEffect::Blessed) {
+            add_weight(&mut weights, EventOutcome::GetLoot, Game::BLESSED_WEIGHT);
+            add_weight(&mut weights, EventOutcome::OK, Game::BLESSED_WEIGHT);
+        }
+        if goblin.has_status(StatusEffect::GreedyFrenzy) {
+            add_weight(&mut weights, EventOutcome::GetLoot, greed);
+            add_weight(&mut weights, EventOutcome::GetAttacked, greed);
+        }
+        // A hero in the event takes revenge on goblins that refused it, or helps ones that bribed it
+        let goblin_index = (self.turn_goblin % self.num_goblins) as usize;
+        let event_hero = self.event_hero();
+        if let Some(h) = event_hero {
+            let hero = &self.heroes[h];
+            let bonus = Game::HERO_EVENT_WEIGHT;
+            if hero.holds_grudge(goblin_index) {
+                add_weight(&mut weights, EventOutcome::GetAttacked, bonus);
+                add_weight(&mut weights, Game::HERO_GRUDGE_OUTCOMES[h], bonus);
+            } else if hero.is_friendly_to(goblin_index) {
+                add_weight(&mut weights, Game::HERO_FAVOR_OUTCOMES[h], bonus);
+            }
+        }
+        // The event itself makes some outcomes likelier than others
+        if let Some(event) = EventDef::get(self.event) {
+            for (outcome, modifier) in event.weight_modifiers {
+                let weight = &mut weights[*outcome as usize];
+                *weight = weight.saturating_add_signed(*modifier as i16);
+            }
+        }
+        // If the goblin isn't risking, reduce reward and make OK outcome very likely
+        if !risk_it {
+            weights[EventOutcome::GetLoot as usize] = 0;
+            weights[EventOutcome::GetItem as usize] = 0;
+            weights[EventOutcome::StealLoot as usize] = 0;
+            weights[EventOutcome::StealItem as usize] = 0;
+            weights[EventOutcome::Heal as usize] = 0;
+            weights[EventOutcome::BoostLuck as usize] = 0;
+            weights[EventOutcome::ReduceGreed as usize] = 0;
+            weights[EventOutcome::OK as usize] = Game::SAFE_OK_WEIGHT;
+        }
+        // Calculate outcome
+        let total_weight = weights
+            .iter()
+            .fold(0u16, |total, weight| total.saturating_add(*weight));
+        let roll = dice.roll_u16(total_weight);
+        let mut outcome = EventOutcome::OK;
+        let mut offset = 0;
+        for (i, weight) in weights.iter().enumerate() {
+            if roll >= offset && roll < offset.saturating_add(*weight) {
+                outcome = unsafe { std::mem::transmute(i as u8) };
+                break;
+            }
+            offset = offset.saturating_add(*weight);
+        }
+        self.set_event_outcome(outcome);
+        msg!("{:?}", outcome);
+        // Revenge settles the hero's grudge
+        if let Some(h) = event_hero {
+            let hero = &mut self.heroes[h];
+            let is_revenge = matches!(outcome, EventOutcome::GetAttacked)
+                || outcome as u8 == Game::HERO_GRUDGE_OUTCOMES[h] as u8;
+            if is_revenge && hero.holds_grudge(goblin_index) {
+                hero.settle_grudge(goblin_index);
+            }
+        }
+        // Handle outcome
+        match outcome {
+            EventOutcome::GetLoot => {
+                goblin.add_random_loot(dice, self.turn_count, Loot::found_in(outcome));
+                self.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
+            }
+            EventOutcome::GetItem => {
+                goblin.add_random_item(dice);
+                self.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
+            }
+            EventOutcome::StealLoot | EventOutcome::StealItem => {
+                // The goblin picks its victim, if there's anyone to steal from
+                if self.has_targets() {
+                    self.turn_phase = Game::TURN_PHASE_CHOOSE_TARGET;
+                    return Some(outcome);
+                }
+                self.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
+            }
+            EventOutcome::Heal => {
+                goblin.health = goblin.health.saturating_add(1).min(Goblin::MAX_HEALTH);
+                self.aftermath_option = Game::AFTERMATH_OPTION_STOP;
+            }
+            EventOutcome::BoostLuck => {
+                goblin.luck = goblin.luck.saturating_add(1);
+                self.aftermath_option = Game::AFTERMATH_OPTION_STOP;
+            }
+            EventOutcome::ReduceGreed => {
+                goblin.greed = goblin.greed.saturating_sub(1);
+                self.aftermath_option = Game::AFTERMATH_OPTION_STOP;
+            }
+            EventOutcome::LoseLoot => {
+                let _loot = goblin.take_least_valuable_loot();
+                self.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
+            }
+            EventOutcome::LoseItem => {
+                goblin.take_random_item(dice);
+                self.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
+            }
+            EventOutcome::LootGotStolen => {
+                if let Some(i) = self.random_opponent(dice) {
+                    let loot = goblin.take_least_valuable_loot();
+                    self.get_goblin_mut(i).add_loot(loot);
+                }
+                self.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
+            }
+            EventOutcome::ItemGotStolen => {
+                if let Some(thief_index) = self.random_opponent(dice) {
+                    let item = goblin.take_random_item(dice);
+                    // Whatever the thief has no room for stays with the goblin
+                    if !self.get_goblin_mut(thief_index).add_item(item) {
+                        goblin.add_item(item);
+                    }
+                }
+                self.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
+            }
+            EventOutcome::SlapFight => {
+                // Immediately move to slap fight phase
+                self.turn_phase = Game::TURN_PHASE_SLAP_FIGHT;
+                return Some(outcome);
+            }
+            EventOutcome::GetAttacked => {
+                if goblin.has_status(StatusEffect::Reflecting) {
+                    // The attack bounces onto another goblin
+                    if let Some(i) = self.random_opponent(dice) {
+                        self.hurt_goblin(i, 1, dice);
+                    }
+                } else {
+                    self.hurt_goblin(goblin_index, 1, dice);
+                    let _loot = goblin.take_least_valuable_loot();
+                }
+                self.aftermath_option = Game::AFTERMATH_OPTION_STOP;
+            }
+            EventOutcome::OK => {
+                // Nothing happens! :)
+                self.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
+            }
+        }
+        // Move to the aftermath phase
+        self.turn_phase = Game::TURN_PHASE_AFTERMATH;
+        Some(outcome)
+    }
+    /// Steal from the goblin the turn goblin chose after a `StealLoot` or `StealItem`.
+    pub fn choose_target(&mut self, target: usize, dice: &mut Dice) -> Result<()> {
+        if !self.is_target(target) {
+            return err!(LootGoblinError::InvalidTarget);
+        }
+        let goblin = unsafe { (*self.ptr()).get_turn_goblin() };
+        let victim = self.get_goblin_mut(target);
+        // Whatever the goblin has no room for stays with the victim
+        let stolen = if self.event_outcome == EventOutcome::StealItem as u8 {
+            let item = victim.take_random_item(dice);
+            let stolen = goblin.add_item(item);
+            if item > 0 {
+                if !stolen {
+                    victim.add_item(item);
+                }
+                goblin.greed = goblin.greed.saturating_add(1);
+            }
+            stolen
+        } else {
+            let loot = victim.take_least_valuable_loot();
+            victim.luck = victim.luck.saturating_add(1);
+            let stolen = goblin.add_loot(loot);
+            if !stolen {
+                victim.add_loot(loot);
+            }
+            stolen
+        };
+        // Only goblins that actually lost something count as robbed
+        if stolen {
+            self.record_theft(target);
+        }
+        self.aftermath_option = dice.roll(Game::AFTERMATH_OPTION_LEN);
+        self.turn_phase = Game::TURN_PHASE_AFTERMATH;
+        Ok(())
+    }
+    pub fn make_aftermath_decision(&mut self, choice: u8, dice: &mut Dice) {
+        // Continue or stop
+        match (self.aftermath_option, choice) {
+            (Game::AFTERMATH_OPTION_CONTINUE, _)
+            | (Game::AFTERMATH_OPTION_EITHER, Game::AFTERMATH_OPTION_CONTINUE) => {
+                if !self.get_turn_goblin().has_items() {
+                    // Move to outcome phase w new event
+                    self.new_random_event(dice);
+                    self.turn_phase = Game::TURN_PHASE_OUTCOME;
+                } else {
+                    self.turn_phase = Game::TURN_PHASE_ITEM;
+                }
+            }
+            _ => {
+                self.advance_to_next_goblin();
+                self.start_turn();
+            }
+        }
+    }
+    pub fn slap_fight(&mut self, dice: &mut Dice) {
+        if self.is_coop() {
+            return self.attack_boss(dice);
+        }
+        if self.is_team_game() {
+            return self.team_slap_fight(dice);
+        }
+        // Each goblin still in the game rolls, find the highest and lowest rolls
+        let fighters: Vec<usize> = (0..self.num_goblins as usize)
+            .filter(|i| !self.is_out(*i))
+            .collect();
+        let rolls = dice.roll_unique(Dice::D10, fighters.len());
+        let mut highest_roll = 0;
+        let mut lowest_roll = u8::MAX;
+        let mut highest_goblin_index = 0;
+        let mut lowest_goblin_index = 0;
+        for (i, roll) in fighters.iter().copied().zip(rolls.iter()) {
+            self.goblins[i].last_roll = *roll;
+            self.goblins[i].last_roll_at = self.turn_count;
+            if *roll > highest_roll {
+                highest_roll = *roll;
+                highest_goblin_index = i;
+            }
+            if *roll < lowest_roll {
+                lowest_roll = *roll;
+                lowest_goblin_index = i;
+            }
+        }
+        // Increase the greed of every goblin in the fight
+        for i in fighters {
+            self.goblins[i].greed = self.goblins[i].greed.saturating_add(1);
+        }
+        // The goblin with the highest roll takes loot from the one with the lowest roll
+        if highest_goblin_index != lowest_goblin_index {
+            self.record_slap_fight_win(highest_goblin_index);
+            let loot = self.goblins[lowest_goblin_index].take_least_valuable_loot();
+            self.goblins[highest_goblin_index].add_loot(loot);
+            // decrease loser greed
+            self.goblins[lowest_goblin_index].greed =
+                self.goblins[lowest_goblin_index].greed.saturating_sub(1);
+            // Increase winner greed
+            self.goblins[highest_goblin_index].greed =
+                self.goblins[highest_goblin_index].greed.saturating_add(1);
+        }
+
+        // Start the next goblin's turn
+        self.advance_to_next_goblin();
+        self.start_turn();
+    }
+    /// Turn the pending slap fight into one the goblins wager on.
+    pub fn open_wagered_slap_fight(&mut self) -> Result<()> {
+        if self.game_mode != GameMode::Competitive as u8 {
+            return err!(LootGoblinError::WagersNotAllowed);
+        }
+        self.turn_phase = Game::TURN_PHASE_WAGERED_SLAP_FIGHT;
+        Ok(())
+    }
+    /// How hard the goblin at `i` slaps: a D10 boosted by its luck, greed and gear.
+    pub fn slap_power(&self, i: usize, dice: &mut Dice) -> u8 {
+        let goblin = &self.goblins[i];
+        let mut power = 1 + dice.roll(Dice::D10);
+        power += goblin.luck.min(Game::MAX_SLAP_LUCK_BONUS);
+        power += goblin.greed / Game::GREED_PER_SLAP_BONUS;
+        match goblin.equipped_item() {
+            Game::ITEM_SHIELD => power += 2,
+            Game::ITEM_RING_OF_REFLECT => power += 1,
+            _ => {}
+        }
+        if goblin.has_status(StatusEffect::Cursed) {
+            power /= 2;
+        }
+        power
+    }
+    /// Settle a wagered slap fight. The strongest slapper among the goblins that revealed
+    /// takes the pot plus a piece of loot from each goblin that didn't. Ties hand every
+    /// wager back.
+    pub fn resolve_wagered_slap_fight(&mut self, fight: &WageredSlapFight, dice: &mut Dice) {
+        let num_goblins = self.num_goblins as usize;
+        let mut powers = vec![0u8; num_goblins];
+        for (i, power) in powers.iter_mut().enumerate() {
+            // Goblins that are out sit the fight out
+            if fight.has_revealed(i) && !self.is_out(i) {
+                *power = self.slap_power(i, dice);
+                self.goblins[i].last_roll = *power;
+                self.goblins[i].last_roll_at = self.turn_count;
+            }
+        }
+        let best = powers.iter().copied().max().unwrap_or(0);
+        let winner = match powers.iter().filter(|power| **power == best).count() {
+            1 if best > 0 => powers.iter().position(|power| *power == best),
+            _ => None,
+        };
+        for i in 0..num_goblins {
+            if !self.is_out(i) {
+                self.goblins[i].greed = self.goblins[i].greed.saturating_add(1);
+            }
+            // Whatever the winner can't carry goes back to its owner
+            let taker = winner.unwrap_or(i);
+            if !self.goblins[taker].add_loot(fight.pot_loot[i]) {
+                self.goblins[i].add_loot(fight.pot_loot[i]);
+            }
+            if fight.pot_items[i] > 0 && !self.goblins[taker].add_item(fight.pot_items[i]) {
+                self.goblins[i].add_item(fight.pot_items[i]);
+            }
+        }
+        if let Some(winner) = winner {
+            self.record_slap_fight_win(winner);
+            for i in (0..num_goblins).filter(|i| !fight.has_revealed(*i)) {
+                let loot = self.goblins[i].take_least_valuable_loot();
+                self.goblins[winner].add_loot(loot);
+            }
+            self.goblins[winner].greed = self.goblins[winner].greed.saturating_add(1);
+        }
+
+        // Start the next goblin's turn
+        self.advance_to_next_goblin();
+        self.start_turn();
+    }
+    /// In team games the teams slap it out. The team with the higher total roll has its
+    /// best slapper take loot from the other team's worst.
+    pub fn team_slap_fight(&mut self, dice: &mut Dice) {
+        let mut totals = [0u16; Game::NUM_TEAMS];
+        let mut best = [(0u8, 0usize); Game::NUM_TEAMS];
+        let mut worst = [(u8::MAX, 0usize); Game::NUM_TEAMS];
+        let mut fighters = [0u8; Game::NUM_TEAMS];
+        let out: Vec<bool> = (0..self.num_goblins as usize)
+            .map(|i| self.is_out(i))
+            .collect();
+        for (i, goblin) in self.goblins[..self.num_goblins as usize]
+            .iter_mut()
+            .enumerate()
+        {
+            // Goblins that are out sit the fight out
+            if out[i] {
+                continue;
+            }
+            let roll = dice.roll(Dice::D10);
+            goblin.last_roll = roll;
+            goblin.last_roll_at = self.turn_count;
+            goblin.greed = goblin.greed.saturating_add(1);
+            let team = goblin.team as usize;
+            fighters[team] += 1;
+            totals[team] += roll as u16;
+            if roll >= best[team].0 {
+                best[team] = (roll, i);
+            }
+            if roll < worst[team].0 {
+                worst[team] = (roll, i);
+            }
+        }
+        // A team with nobody left standing can't be slapped
+        if totals[0] != totals[1] && fighters.iter().all(|count| *count > 0) {
+            let winners = if totals[0] > totals[1] { 0 } else { 1 };
+            let (winner, loser) = (best[winners].1, worst[1 - winners].1);
+            self.record_slap_fight_win(winner);
+            let loot = self.goblins[loser].take_least_valuable_loot();
+            self.goblins[winner].add_loot(loot);
+            self.goblins[loser].greed = self.goblins[loser].greed.saturating_sub(1);
+            self.goblins[winner].greed = self.goblins[winner].greed.saturating_add(1);
+        }
+
+        // Start the next goblin's turn
+        self.advance_to_next_goblin();
+        self.start_turn();
+    }
+    /// In co-op games every goblin slaps the boss instead of each other. Each goblin that
+    /// out-rolls the boss hurts it, and the boss hits back at the lowest roller.
+    pub fn attack_boss(&mut self, dice: &mut Dice) {
+        let boss_roll = dice.roll(Dice::D10);
+        let mut lowest_roll = u8::MAX;
+        let mut lowest_goblin_index = 0;
+        let out: Vec<bool> = (0..self.num_goblins as usize)
+            .map(|i| self.is_out(i))
+            .collect();
+        for (i, goblin) in self.goblins[..self.num_goblins as usize]
+            .iter_mut()
+            .enumerate()
+        {
+            // Goblins that are out sit the fight out
+            if out[i] {
+                continue;
+            }
+            let roll = dice.roll(Dice::D10);
+            goblin.last_roll = roll;
+            goblin.last_roll_at = self.turn_count;
+            if roll > boss_roll && self.boss_health > 0 {
+                self.boss_health -= 1;
+                goblin.boss_damage = goblin.boss_damage.saturating_add(1);
+            }
+            if roll < lowest_roll {
+                lowest_roll = roll;
+                lowest_goblin_index = i;
+            }
+        }
+        if boss_roll > lowest_roll {
+            self.hurt_goblin(lowest_goblin_index, 1, dice);
+        }
+        // Defeating the boss ends the crawl early
+        if self.boss_health == 0 {
+            self.boss_defeated_at = self.turn_count;
+            self.end_crawl();
+            return;
+        }
+        // Start the next goblin's turn
+        self.advance_to_next_goblin();
+        self.start_turn();
+    }
+    /// Pool every goblin's loot and deal it back out, most valuable first, to whichever
+    /// goblin is furthest below its share. Shares follow the damage dealt to the boss.
+    pub fn split_shared_loot(&mut self) {
+        let goblins = &mut self.goblins[..self.num_goblins as usize];
+        let mut pool: Vec<Loot> = goblins
+            .iter_mut()
+            .flat_map(|goblin| goblin.loot_bag.iter_mut())
+            .filter(|loot| !loot.is_empty())
+            .map(std::mem::take)
+            .collect();
+        pool.sort_by_key(|loot| std::cmp::Reverse(loot.value()));
+        let total_value: u32 = pool.iter().map(|loot| loot.value() as u32).sum();
+        let total_damage: u32 = goblins.iter().map(|goblin| goblin.boss_damage as u32).sum();
+        let shares: Vec<u32> = goblins
+            .iter()
+            .map(|goblin| match total_damage {
+                0 => total_value / goblins.len() as u32,
+                _ => total_value * goblin.boss_damage as u32 / total_damage,
+            })
+            .collect();
+        let mut received = vec![0u32; goblins.len()];
+        for loot in pool {
+            let taker = (0..goblins.len())
+                .filter(|i| goblins[*i].has_room_for_loot())
+                .max_by_key(|i| shares[*i] as i64 - received[*i] as i64);
+            if let Some(i) = taker {
+                goblins[i].add_loot(loot);
+                received[i] += loot.value() as u32;
+            }
+        }
+    }
+    /// Use the turn goblin's once-per-game class ability.
+    pub fn use_class_ability(&mut self, dice: &mut Dice) -> Result<()> {
+        let goblin = unsafe { (*self.ptr()).get_turn_goblin() };
+        if goblin.ability_used != 0 {
+            return err!(LootGoblinError::AbilityAlreadyUsed);
+        }
+        match GoblinClass::from_u8(goblin.class) {
+            // Take loot from an opponent
+            Some(GoblinClass::Sneak) => {
+                if let Some(i) = self.random_opponent(dice) {
+                    let victim = self.get_goblin_mut(i);
+                    let loot = victim.take_least_valuable_loot();
+                    if goblin.add_loot(loot) {
+                        self.record_theft(i);
+                    } else {
+                        victim.add_loot(loot);
+                    }
+                }
+            }
+            // Hurt an opponent
+            Some(GoblinClass::Brute) => {
+                if let Some(i) = self.random_opponent(dice) {
+                    self.hurt_goblin(i, 1, dice);
+                }
+            }
+            // Ward off harm
+            Some(GoblinClass::Shaman) => {
+                goblin.apply_status(StatusEffect::Blessed, StatusEffect::DURATION);
+                goblin.apply_status(StatusEffect::Shielded, StatusEffect::DURATION);
+            }
+            // Dig up some loot
+            Some(GoblinClass::Hoarder) => {
+                for _ in 0..Game::HOARDER_STASH_SIZE {
+                    goblin.add_random_loot(dice, self.turn_count, Loot::FOUND_RUMMAGING);
+                }
+            }
+            Some(GoblinClass::None) | None => return err!(LootGoblinError::InvalidClass),
+        }
+        goblin.ability_used = 1;
+        Ok(())
+    }
+    /// Whether the turn goblin can steal from or hurt the goblin at `i`: any goblin
+    /// still standing other than itself and its teammates.
+    pub fn is_target(&self, i: usize) -> bool {
+        let turn_goblin = (self.turn_goblin % self.num_goblins) as usize;
+        i < self.num_goblins as usize
+            && i != turn_goblin
+            && !self.are_teammates(i, turn_goblin)
+            && !self.goblins[i].is_fainted()
+    }
+    pub fn has_targets(&self) -> bool {
+        (0..self.num_goblins as usize).any(|i| self.is_target(i))
+    }
+    /// A random goblin the turn goblin can target.
+    pub fn random_opponent(&self, dice: &mut Dice) -> Option<usize> {
+        let opponents: Vec<usize> = (0..self.num_goblins as usize)
+            .filter(|i| self.is_target(*i))
+            .collect();
+        if opponents.is_empty() {
+            return None;
+        }
+        Some(opponents[dice.roll(opponents.len() as u8) as usize])
+    }
+    /// Damage the goblin at `i`. If that knocks it out it counts a faint and scatters
+    /// some of its loot among the others, as the game's [FaintRules] say.
+    pub fn hurt_goblin(&mut self, i: usize, amount: u8, dice: &mut Dice) {
+        if self.goblins[i].is_fainted() {
+            return;
+        }
+        self.goblins[i].take_damage(amount);
+        if !self.goblins[i].is_fainted() {
+            return;
+        }
+        let goblin = self.get_goblin_mut(i);
+        goblin.faints = goblin.faints.saturating_add(1);
+        let pieces = goblin
+            .loot_bag
+            .iter()
+            .filter(|loot| !loot.is_empty())
+            .count();
+        for _ in 0..self.faint_rules.loot_to_scatter(pieces) {
+            let others: Vec<usize> = (0..self.num_goblins as usize)
+                .filter(|j| *j != i && !self.is_out(*j) && self.goblins[*j].has_room_for_loot())
+                .collect();
+            if others.is_empty() {
+                break;
+            }
+            let loot = goblin.take_least_valuable_loot();
+            let j = others[dice.roll(others.len() as u8) as usize];
+            self.get_goblin_mut(j).add_loot(loot);
+        }
     }
+    /// CPU goblins push their luck for a couple of events while they're unhurt.
+    pub fn cpu_should_risk_it(&self) -> bool {
+        self.get_turn_goblin().health == Goblin::MAX_HEALTH && self.turn_risks < Game::CPU_MAX_RISKS
+    }
+    /// Note that the turn goblin stole from the goblin at `victim`, and whether it has now
+    /// robbed every opponent.
+    pub fn record_theft(&mut self, victim: usize) {
+        let thief = (self.turn_goblin % self.num_goblins) as usize;
+        self.goblins[thief].stolen_from |= 1 << victim;
+        let stolen_from = self.goblins[thief].stolen_from;
+        let robbed_everyone = (0..self.num_goblins as usize)
+            .filter(|i| *i != thief && !self.are_teammates(*i, thief))
+            .all(|i| stolen_from & (1 << i) != 0);
+        if robbed_everyone {
+            self.goblins[thief].unlock(Achievement::RobbedEveryone);
+        }
+    }
+    pub fn record_slap_fight_win(&mut self, winner: usize) {
+        if self.goblins[winner].health == 1 {
+            self.goblins[winner].unlock(Achievement::LastGaspSlap);
+        }
+    }
+    /// Whether the goblin at `i` has fainted too often to keep playing.
+    pub fn is_out(&self, i: usize) -> bool {
+        self.faint_rules.is_out(self.goblins[i].faints)
+    }
+    pub fn goblins_standing(&self) -> usize {
+        (0..self.num_goblins as usize)
+            .filter(|i| !self.is_out(*i))
+            .count()
+    }
+    /// Ends the turn goblin's turn, counting down its status effects.
     pub fn advance_to_next_goblin(&mut self) {
+        self.get_turn_goblin().tick_status_effects();
         self.turn_goblin = (self.turn_goblin + 1) % self.num_goblins;
     }
+    pub fn max_turns(&self) -> u8 {
+        (self.game_rounds as u16 * self.num_goblins as u16).min(u8::MAX as u16) as u8
+    }
     pub fn start_turn(&mut self) {
+        // The crawl ends once every goblin has played all of its rounds, or when only one
+        // is left standing in an elimination game
+        let last_one_standing = self.faint_rules.is_elimination()
+            && self.num_goblins > 1
+            && self.goblins_standing() <= 1;
+        if self.turn_count >= self.max_turns() || last_one_standing {
+            self.end_crawl();
+            return;
+        }
+        // Goblins that are out of the game sit their turns out, without using up a turn,
+        // a restock, or a hero roam
+        if self.is_out(self.turn_goblin as usize) && self.goblins_standing() > 0 {
+            self.advance_to_next_goblin();
+            return self.start_turn();
+        }
         self.turn_phase = Game::TURN_PHASE_RUMMAGE;
         self.turn_events = 0;
+        self.turn_risks = 0;
         self.turn_count += 1;
         let mut dice = Dice::new();
         self.rummage_success_min = dice.roll(Dice::D10);
+        // The shop restocks at the start of every round
+        if (self.turn_count - 1) % self.num_goblins == 0 {
+            self.restock_shop(&mut dice);
+        }
+        for hero in self.heroes.iter_mut() {
+            hero.roam(&self.rooms[hero.position as usize], &mut dice);
+        }
         let goblin = self.get_turn_goblin();
-        if goblin.health == 0 {
-            goblin.health = Goblin::MAX_HEALTH;
+        if goblin.is_fainted() {
+            goblin.health = self.faint_rules.recovered_health();
+            // Shaking off a faint can take the whole turn
+            if self.faint_rules.skip_turn != 0 {
+                self.advance_to_next_goblin();
+                self.start_turn();
+            }
         }
     }
+    pub fn end_crawl(&mut self) {
+        self.game_phase = Game::GAME_PHASE_CRAWL_ENDED;
+        self.ended_at = Clock::get().expect("couldn't get clock").unix_timestamp;
+        // Hand back anything still escrowed so it counts towards final scores
+        for i in 0..self.num_goblins as usize {
+            self.return_trade_escrow(i);
+        }
+        // A victorious party shares the spoils
+        if self.is_coop() && self.boss_defeated_at > 0 {
+            self.split_shared_loot();
+        }
+        for goblin in self.goblins[..self.num_goblins as usize].iter_mut() {
+            if !goblin.has_room_for_loot() {
+                goblin.unlock(Achievement::BagFull);
+            }
+        }
+        if self.is_team_game() {
+            self.team_scores = Default::default();
+            for goblin in self.goblins[..self.num_goblins as usize].iter() {
+                self.team_scores[goblin.team as usize] += goblin.score();
+            }
+        }
+        // Carry the result over to the series
+        for i in 0..self.num_goblins as usize {
+            self.series_scores[i] = self.series_scores[i].saturating_add(self.goblins[i].score());
+        }
+        let winner = self.standings()[0];
+        self.series_wins[winner] += 1;
+        if self.series_wins[winner] > self.best_of / 2 && self.best_of > 0 {
+            self.series_winner = winner as u8 + 1;
+        }
+    }
+    pub fn is_series_over(&self) -> bool {
+        self.series_winner != 0
+    }
+    /// Reset a finished game in place for the next game of a best-of-`best_of` series,
+    /// keeping its players, their classes, settings and series standings. The first turn
+    /// passes to the next goblin over.
+    pub fn rematch(&mut self, best_of: u8) -> Result<()> {
+        // The first rematch decides the length of the series
+        let is_new_series = self.best_of == 0;
+        if is_new_series && (!(3..=Game::MAX_BEST_OF).contains(&best_of) || best_of % 2 == 0) {
+            return err!(LootGoblinError::InvalidSeriesLength);
+        }
+        if !is_new_series && best_of != self.best_of {
+            return err!(LootGoblinError::InvalidSeriesLength);
+        }
+        if self.is_series_over() || self.series_game + 1 >= best_of {
+            return err!(LootGoblinError::SeriesOver);
+        }
+        let num_goblins = self.num_goblins;
+        // Every seat keeps its player, CPU goblins included, so series scores line up
+        let players: Vec<Pubkey> = self.goblins[..num_goblins as usize]
+            .iter()
+            .map(|goblin| goblin.player)
+            .collect();
+        let classes = self.goblins.map(|goblin| goblin.class);
+        let (bump, creator, id) = (self.bump, self.creator, self.id);
+        let (wide_id, seed_scheme, migrated) = (self.wide_id, self.seed_scheme, self.migrated);
+        let (claimed_loot, side_bet_markets) = (self.claimed_loot, self.side_bet_markets);
+        let (season, created_at, created_slot) = (self.season, self.created_at, self.created_slot);
+        let (game_rounds, game_mode, faint_rules) =
+            (self.game_rounds, self.game_mode, self.faint_rules);
+        let (series_scores, series_wins) = (self.series_scores, self.series_wins);
+        let series_game = self.series_game + 1;
+        let first_goblin = (self.first_goblin + 1) % num_goblins;
+        // Wipe the finished game without copying it onto the stack
+        bytemuck::bytes_of_mut(self).fill(0);
+        self.bump = bump;
+        self.creator = creator;
+        self.id = id;
+        self.wide_id = wide_id;
+        self.seed_scheme = seed_scheme;
+        self.migrated = migrated;
+        self.claimed_loot = claimed_loot;
+        self.side_bet_markets = side_bet_markets;
+        self.season = season;
+        self.created_at = created_at;
+        self.created_slot = created_slot;
+        self.game_rounds = game_rounds;
+        self.game_mode = game_mode;
+        self.faint_rules = faint_rules;
+        self.series_scores = series_scores;
+        self.series_wins = series_wins;
+        self.series_game = series_game;
+        self.best_of = best_of;
+        self.first_goblin = first_goblin;
+        self.recruit(num_goblins, &players)?;
+        for (goblin, class) in self.goblins.iter_mut().zip(classes) {
+            goblin.class = class;
+        }
+        Ok(())
+    }
+    /// Escrow what the goblin at `from` puts up and open a trade with another goblin.
+    pub fn offer_trade(&mut self, from: usize, offer: &TradeOffer) -> Result<()> {
+        let to = offer.with_goblin as usize;
+        if from >= self.num_goblins as usize || to >= self.num_goblins as usize {
+            return err!(LootGoblinError::InvalidGoblinIndex);
+        }
+        // Anyone can control a CPU goblin, so they can't trade
+        if from == to || self.goblins[from].is_cpu() || self.goblins[to].is_cpu() {
+            return err!(LootGoblinError::InvalidTrade);
+        }
+        if self.trades[from].is_open() {
+            return err!(LootGoblinError::TradeAlreadyOpen);
+        }
+        let goblin = &self.goblins[from];
+        let other = &self.goblins[to];
+        let mut trade = Trade {
+            is_open: 1,
+            with_goblin: offer.with_goblin,
+            offered_at_turn: self.turn_count,
+            ..Default::default()
+        };
+        if let Some(i) = offer.offered_loot_index {
+            trade.offered_loot = goblin.loot_bag.get(i as usize).copied().unwrap_or_default();
+            if trade.offered_loot.is_empty() {
+                return err!(LootGoblinError::InvalidTrade);
+            }
+        }
+        if let Some(slot) = offer.offered_item_slot {
+            trade.offered_item = goblin
+                .inventory
+                .get(slot as usize)
+                .map_or(0, |stack| stack.item);
+            if trade.offered_item == 0 {
+                return err!(LootGoblinError::InvalidTrade);
+            }
+        }
+        if let Some(i) = offer.requested_loot_index {
+            trade.requested_loot = other.loot_bag.get(i as usize).copied().unwrap_or_default();
+            if trade.requested_loot.is_empty() {
+                return err!(LootGoblinError::InvalidTrade);
+            }
+            trade.requested_loot_index = i;
+        }
+        if let Some(slot) = offer.requested_item_slot {
+            trade.requested_item = other
+                .inventory
+                .get(slot as usize)
+                .map_or(0, |stack| stack.item);
+            if trade.requested_item == 0 {
+                return err!(LootGoblinError::InvalidTrade);
+            }
+        }
+        if trade.offered_loot.is_empty()
+            && trade.offered_item == 0
+            && trade.requested_loot.is_empty()
+            && trade.requested_item == 0
+        {
+            return err!(LootGoblinError::InvalidTrade);
+        }
+        // Escrow the offer
+        let goblin = &mut self.goblins[from];
+        if let Some(i) = offer.offered_loot_index {
+            goblin.loot_bag[i as usize] = Loot::default();
+        }
+        if let Some(slot) = offer.offered_item_slot {
+            goblin.take_item(slot as usize);
+        }
+        self.trades[from] = trade;
+        Ok(())
+    }
+    /// Swap the escrowed offer of the goblin at `from` for what it asked for.
+    pub fn accept_trade(&mut self, from: usize) -> Result<()> {
+        let trade = self.trades[from];
+        if !trade.is_open() {
+            return err!(LootGoblinError::NoOpenTrade);
+        }
+        let to = trade.with_goblin as usize;
+        let goblin = &self.goblins[from];
+        let other = &self.goblins[to];
+        let gives_loot = !trade.requested_loot.is_empty();
+        let gives_item = trade.requested_item != 0;
+        // The other goblin must still have what was asked for
+        if gives_loot && other.loot_bag[trade.requested_loot_index as usize] != trade.requested_loot
+        {
+            return err!(LootGoblinError::TradeNoLongerValid);
+        }
+        if gives_item && other.find_item(trade.requested_item).is_none() {
+            return err!(LootGoblinError::TradeNoLongerValid);
+        }
+        // Both goblins need room for what they receive
+        if (gives_loot && !goblin.has_room_for_loot())
+            || (gives_item && !goblin.has_room_for_item(trade.requested_item))
+            || (!trade.offered_loot.is_empty() && !gives_loot && !other.has_room_for_loot())
+            || (trade.offered_item != 0 && !other.has_room_for_item(trade.offered_item))
+        {
+            return err!(LootGoblinError::NoRoomForTrade);
+        }
+        let other = &mut self.goblins[to];
+        if gives_loot {
+            other.loot_bag[trade.requested_loot_index as usize] = Loot::default();
+        }
+        if let Some(slot) = other.find_item(trade.requested_item) {
+            other.take_item(slot);
+        }
+        other.add_loot(trade.offered_loot);
+        other.add_item(trade.offered_item);
+        let goblin = &mut self.goblins[from];
+        goblin.add_loot(trade.requested_loot);
+        goblin.add_item(trade.requested_item);
+        self.trades[from] = Trade::default();
+        Ok(())
+    }
+    /// Close the open trade of the goblin at `from`, returning its escrow.
+    pub fn cancel_trade(&mut self, from: usize) -> Result<()> {
+        if !self.trades[from].is_open() {
+            return err!(LootGoblinError::NoOpenTrade);
+        }
+        self.return_trade_escrow(from);
+        Ok(())
+    }
+    /// Anything that no longer fits in the goblin's bag or inventory is lost.
+    fn return_trade_escrow(&mut self, from: usize) {
+        let trade = self.trades[from];
+        let goblin = &mut self.goblins[from];
+        goblin.add_loot(trade.offered_loot);
+        goblin.add_item(trade.offered_item);
+        self.trades[from] = Trade::default();
+    }
+    pub fn restock_shop(&mut self, dice: &mut Dice) {
+        for item in self.available_items.iter_mut() {
+            *item = 1 + dice.roll(Game::NUM_ITEMS);
+        }
+    }
+    pub fn item_price(item: u8) -> u16 {
+        Game::ITEM_PRICES[(item - 1) as usize]
+    }
+    /// Buy the shop item at `shop_index` for the turn goblin, paying with the loot at
+    /// `loot_indices`. Overpaying doesn't give any change.
+    pub fn buy_item(&mut self, shop_index: usize, loot_indices: &[u8]) -> Result<()> {
+        let goblin = unsafe { (*self.ptr()).get_turn_goblin() };
+        let Some(item) = self
+            .available_items
+            .get_mut(shop_index)
+            .filter(|item| **item > 0)
+        else {
+            return err!(LootGoblinError::ItemNotAvailable);
+        };
+        if !goblin.has_room_for_item(*item) {
+            return err!(LootGoblinError::InventoryFull);
+        }
+        let mut paid = 0;
+        for (i, loot_index) in loot_indices.iter().enumerate() {
+            let loot = goblin
+                .loot_bag
+                .get(*loot_index as usize)
+                .copied()
+                .unwrap_or_default();
+            if loot.is_empty() || loot_indices[..i].contains(loot_index) {
+                return err!(LootGoblinError::InvalidLootIndex);
+            }
+            paid += loot.value();
+        }
+        if paid < Game::item_price(*item) {
+            return err!(LootGoblinError::NotEnoughLoot);
+        }
+        for loot_index in loot_indices {
+            goblin.loot_bag[*loot_index as usize] = Loot::default();
+        }
+        goblin.add_item(std::mem::take(item));
+        Ok(())
+    }
+    /// Goblin indices ordered from highest to lowest final score. In team games the
+    /// winning team's goblins come first, and goblins that are out come last.
+    pub fn standings(&self) -> Vec<usize> {
+        let mut standings: Vec<usize> = (0..self.num_goblins as usize).collect();
+        let team_score = |i: usize| {
+            if self.is_team_game() {
+                self.team_scores[self.goblins[i].team as usize]
+            } else {
+                0
+            }
+        };
+        let rank = |i: usize| (team_score(i), !self.is_out(i), self.goblins[i].score());
+        standings.sort_by_key(|i| std::cmp::Reverse(rank(*i)));
+        standings
+    }
 }
 
 #[derive(Debug, Copy, Clone, Zeroable, Pod, AnchorSerialize, AnchorDeserialize, PartialEq, Eq)]
@@ -136,51 +1604,247 @@ pub struct Goblin {
     pub greed: u8,
     pub last_roll: u8,
     pub last_roll_at: u8,
-    pub held_item: u8, // held item id
-    pub loot_bag: [u8; 32],
+    pub equipped: u8, // inventory slot + 1 of the equipped item, 0 - none
+    pub loot_bag: [Loot; 32],
+    pub inventory: [ItemStack; 4],
+    pub status_effects: [u8; StatusEffect::LEN], // remaining turns per StatusEffect
+    pub class: u8,                               // GoblinClass
+    pub ability_used: u8,                        // 1 - the class ability has been used this game
+    pub room: u8,                                // room of the dungeon the goblin is in
+    pub moved_at: u8,                            // turn_count of the goblin's last move
+    pub boss_damage: u8,                         // co-op only, damage dealt to the boss
+    pub team: u8,                                // team games only, 0 or 1
+    pub faints: u8,                              // times the goblin's health hit 0
+    pub safe_plays: u8,                          // events the goblin played it safe on
+    pub stolen_from: u8,                         // bitset of goblins it has stolen from
+    pub achievements: u8,                        // bitset of Achievement earned this game
 }
 impl Goblin {
     pub const MAX_HEALTH: u8 = 2;
+    pub const SAFE_PLAY_PENALTY_PERCENT: u8 = 5;
+    pub const MIN_SCORE_PERCENT: u8 = 50;
+    pub const CLAIMABLE_LOOT: usize = 3; // most valuable pieces that can be minted as NFTs
     pub fn init(&mut self, player: Pubkey) {
         self.player = player;
         self.health = Self::MAX_HEALTH;
     }
-    pub fn can_be_controlled_by(&self, player: Pubkey) -> bool {
+    pub fn can_be_controlled_by(&self, signer: Pubkey, session: Option<&Session>) -> bool {
         // Anyone can control a CPU goblin
-        if self.player == Pubkey::default() {
+        if self.is_cpu() {
             return true;
         }
         // Check if signer is goblin's player
-        return self.player == player;
+        if self.player == signer {
+            return true;
+        }
+        // Check if signer holds a live session key delegated by the goblin's player
+        session.is_some_and(|session| session.player == self.player && session.is_valid_for(signer))
+    }
+    pub fn unlock(&mut self, achievement: Achievement) {
+        self.achievements |= achievement.bit();
+    }
+    pub fn is_fainted(&self) -> bool {
+        self.health == 0
+    }
+    pub fn is_cpu(&self) -> bool {
+        self.player == Pubkey::default()
     }
-    pub fn add_loot(&mut self, loot: u8) -> bool {
-        for n in &mut self.loot_bag {
-            if *n == 0 {
-                *n = loot.min(Dice::LOOT + 1);
-                return true;
+    pub fn loot_value(&self) -> u16 {
+        self.loot_bag.iter().map(|loot| loot.value()).sum()
+    }
+    pub fn set_bonus(&self) -> u16 {
+        // Track which pieces of each category's set the goblin holds
+        let mut pieces = [0u8; LootCategory::LEN];
+        for loot in self.loot_bag.iter().filter(|loot| !loot.is_empty()) {
+            if let Some(set) = pieces.get_mut(loot.category as usize) {
+                *set |= 1 << loot.id;
             }
         }
-        return false;
+        let full_set = (1u8 << Loot::SET_SIZE) - 1;
+        let num_sets = pieces.iter().filter(|set| **set == full_set).count();
+        num_sets as u16 * Loot::SET_BONUS
+    }
+    /// Final score multiplier, as a percentage. Every safe play costs a little of it.
+    pub fn score_percent(&self) -> u8 {
+        let penalty = self.safe_plays as u16 * Goblin::SAFE_PLAY_PENALTY_PERCENT as u16;
+        100 - penalty.min(100 - Goblin::MIN_SCORE_PERCENT as u16) as u8
+    }
+    pub fn score(&self) -> u16 {
+        let score = (self.loot_value() + self.set_bonus()) as u32;
+        (score * self.score_percent() as u32 / 100) as u16
     }
-    pub fn add_random_loot(&mut self, dice: &mut Dice) -> bool {
-        for n in &mut self.loot_bag {
-            if *n == 0 {
-                *n = 1 + dice.roll(Dice::LOOT);
-                return true;
+    pub fn add_loot(&mut self, loot: Loot) -> bool {
+        if loot.is_empty() {
+            return false;
+        }
+        match self.loot_bag.iter_mut().find(|slot| slot.is_empty()) {
+            Some(slot) => {
+                *slot = loot;
+                true
             }
+            None => false,
         }
-        return false;
     }
-    pub fn take_least_valuable_loot(&mut self) -> u8 {
-        let loot = *self.loot_bag.iter().min().unwrap();
+    pub fn has_room_for_loot(&self) -> bool {
+        self.loot_bag.iter().any(|slot| slot.is_empty())
+    }
+    pub fn add_random_loot(&mut self, dice: &mut Dice, found_turn: u8, found_outcome: u8) -> bool {
+        self.add_loot(Loot::random(dice, found_turn, found_outcome))
+    }
+    /// Removes and returns the lowest value loot, or empty loot if the bag is empty.
+    pub fn take_least_valuable_loot(&mut self) -> Loot {
         self.loot_bag
             .iter_mut()
-            .find(|n| **n == loot)
-            .map(|n| *n = 0);
-        return loot;
+            .filter(|loot| !loot.is_empty())
+            .min_by_key(|loot| loot.value())
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+    /// Whether the loot at `loot_index` is one of the goblin's `CLAIMABLE_LOOT` most
+    /// valuable pieces. Ties go to the earlier slot.
+    pub fn is_claimable_loot(&self, loot_index: usize) -> bool {
+        let Some(loot) = self.loot_bag.get(loot_index) else {
+            return false;
+        };
+        if loot.is_empty() {
+            return false;
+        }
+        let rank = self
+            .loot_bag
+            .iter()
+            .enumerate()
+            .filter(|(i, other)| {
+                other.value() > loot.value() || (other.value() == loot.value() && *i < loot_index)
+            })
+            .count();
+        rank < Self::CLAIMABLE_LOOT
+    }
+    pub fn has_status(&self, effect: StatusEffect) -> bool {
+        self.status_effects[effect as usize] > 0
+    }
+    /// Applies the effect for the goblin's next `turns` turns, extending it if it's already
+    /// active. When applied during the goblin's own turn, that turn counts as the first.
+    pub fn apply_status(&mut self, effect: StatusEffect, turns: u8) {
+        let remaining = &mut self.status_effects[effect as usize];
+        *remaining = (*remaining).max(turns);
+    }
+    /// Counts down every effect at the end of the goblin's turn, including turns it sits out.
+    pub fn tick_status_effects(&mut self) {
+        for remaining in self.status_effects.iter_mut() {
+            *remaining = remaining.saturating_sub(1);
+        }
+    }
+    /// Deals damage after status effects, returning how much was dealt.
+    pub fn take_damage(&mut self, amount: u8) -> u8 {
+        if self.has_status(StatusEffect::Shielded) {
+            return 0;
+        }
+        let mut damage = amount;
+        if self.has_status(StatusEffect::Poisoned) {
+            damage += 1;
+        }
+        if self.has_status(StatusEffect::Cursed) {
+            damage *= 2;
+        }
+        self.health = self.health.saturating_sub(damage);
+        damage
+    }
+    pub fn has_items(&self) -> bool {
+        self.inventory.iter().any(|stack| !stack.is_empty())
+    }
+    pub fn has_item_in(&self, slot: usize) -> bool {
+        self.inventory
+            .get(slot)
+            .is_some_and(|stack| !stack.is_empty())
+    }
+    pub fn find_item(&self, item: u8) -> Option<usize> {
+        self.inventory
+            .iter()
+            .position(|stack| !stack.is_empty() && stack.item == item)
+    }
+    pub fn has_room_for_item(&self, item: u8) -> bool {
+        self.inventory
+            .iter()
+            .any(|stack| stack.is_empty() || stack.can_stack(item))
+    }
+    /// Stacks the item onto a matching slot, or puts it in the first empty one.
+    pub fn add_item(&mut self, item: u8) -> bool {
+        if item == 0 {
+            return false;
+        }
+        let slot = match self
+            .inventory
+            .iter()
+            .position(|stack| stack.can_stack(item))
+        {
+            Some(slot) => slot,
+            None => match self.inventory.iter().position(ItemStack::is_empty) {
+                Some(slot) => slot,
+                None => return false,
+            },
+        };
+        let stack = &mut self.inventory[slot];
+        stack.item = item;
+        stack.count += 1;
+        true
+    }
+    /// Removes and returns one item from the slot, or 0 if the slot is empty.
+    pub fn take_item(&mut self, slot: usize) -> u8 {
+        let Some(stack) = self
+            .inventory
+            .get_mut(slot)
+            .filter(|stack| !stack.is_empty())
+        else {
+            return 0;
+        };
+        let item = stack.item;
+        stack.count -= 1;
+        if stack.count == 0 {
+            *stack = ItemStack::default();
+            // An emptied slot can't stay equipped
+            if self.equipped as usize == slot + 1 {
+                self.equipped = 0;
+            }
+        }
+        item
+    }
+    /// Removes and returns one item from a random non-empty slot, or 0 if there are none.
+    pub fn take_random_item(&mut self, dice: &mut Dice) -> u8 {
+        let slots: Vec<usize> = (0..self.inventory.len())
+            .filter(|slot| !self.inventory[*slot].is_empty())
+            .collect();
+        if slots.is_empty() {
+            return 0;
+        }
+        let slot = slots[dice.roll(slots.len() as u8) as usize];
+        self.take_item(slot)
+    }
+    pub fn equipped_item(&self) -> u8 {
+        match self.equipped {
+            0 => 0,
+            slot => self.inventory[slot as usize - 1].item,
+        }
+    }
+    pub fn equip(&mut self, slot: usize) -> Result<()> {
+        if !self.has_item_in(slot) {
+            return err!(LootGoblinError::InvalidItemSlot);
+        }
+        self.equipped = slot as u8 + 1;
+        Ok(())
+    }
+    /// Throws away the whole stack in the slot.
+    pub fn drop_items(&mut self, slot: usize) -> Result<()> {
+        if !self.has_item_in(slot) {
+            return err!(LootGoblinError::InvalidItemSlot);
+        }
+        self.inventory[slot] = ItemStack::default();
+        if self.equipped as usize == slot + 1 {
+            self.equipped = 0;
+        }
+        Ok(())
     }
-    pub fn add_random_item(&mut self, dice: &mut Dice) {
-        self.held_item = 1 + dice.roll(Dice::ITEM);
+    pub fn add_random_item(&mut self, dice: &mut Dice) -> bool {
+        self.add_item(1 + dice.roll(Game::NUM_ITEMS))
     }
 }
 
@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(Debug, PartialEq, Eq)]
+pub struct GameCounter {
+    pub bump: u8,
+    pub creator: Pubkey,
+    pub next_id: u64, // id handed out to the creator's next game
+}
+impl GameCounter {
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // bump
+        32 + // creator
+        8; // next_id
+    pub fn take_next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+}
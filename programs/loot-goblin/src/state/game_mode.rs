@@ -0,0 +1,19 @@
+/// How a [crate::state::Game] is won.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum GameMode {
+    Competitive = 0, // every goblin for itself
+    Coop,            // goblins team up against a boss and split the loot
+    Teams,           // 2v2, teams pool their loot for the final score
+}
+impl GameMode {
+    pub const LEN: usize = GameMode::Teams as usize + 1;
+    pub fn from_u8(mode: u8) -> Option<Self> {
+        match mode {
+            0 => Some(Self::Competitive),
+            1 => Some(Self::Coop),
+            2 => Some(Self::Teams),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::error::LootGoblinError;
+
+#[account]
+#[derive(Debug, PartialEq, Eq)]
+pub struct GameRegistry {
+    pub bump: u8,
+    pub open_games: Vec<Pubkey>, // games still recruiting goblins
+}
+impl GameRegistry {
+    pub const MAX_OPEN_GAMES: usize = 64;
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // bump
+        4 + // open_games (len)
+        32 * Self::MAX_OPEN_GAMES; // open_games (entries)
+    pub fn list(&mut self, game: Pubkey) -> Result<()> {
+        if self.open_games.contains(&game) {
+            return Ok(());
+        }
+        if self.open_games.len() >= Self::MAX_OPEN_GAMES {
+            return err!(LootGoblinError::RegistryFull);
+        }
+        self.open_games.push(game);
+        Ok(())
+    }
+    pub fn unlist(&mut self, game: Pubkey) {
+        self.open_games.retain(|open_game| *open_game != game);
+    }
+}
@@ -0,0 +1,23 @@
+/// A goblin's class, giving it a passive edge on some event outcomes and a
+/// once-per-game active ability.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum GoblinClass {
+    None = 0,
+    Sneak,   // steals more, pickpockets an opponent
+    Brute,   // fights more, smashes an opponent
+    Shaman,  // heals more, wards itself
+    Hoarder, // finds more, digs up a stash of loot
+}
+impl GoblinClass {
+    pub const LEN: usize = GoblinClass::Hoarder as usize + 1;
+    pub fn from_u8(class: u8) -> Option<Self> {
+        match class {
+            1 => Some(Self::Sneak),
+            2 => Some(Self::Brute),
+            3 => Some(Self::Shaman),
+            4 => Some(Self::Hoarder),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+use crate::state::Room;
+use crate::utils::Dice;
+
+/// A hero roaming the dungeon. Heroes only deal with goblins in the same room, and
+/// remember which goblins bribed them and which ones refused.
+#[derive(
+    Debug, Copy, Clone, Default, Zeroable, Pod, AnchorSerialize, AnchorDeserialize, PartialEq, Eq,
+)]
+#[repr(C)]
+pub struct Hero {
+    pub position: u8,    // room the hero is in
+    pub mood: u8,        // 0 - hostile to 255 - friendly
+    pub bribed_by: u8,   // bitset of goblin indices that bribed the hero
+    pub refused_by: u8,  // bitset of goblin indices that refused to bribe the hero
+    pub attacked: u8,    // bitset of goblin indices the hero has taken revenge on
+    pub bribe_value: u8, // total value of the loot the hero has been bribed with
+    pub encounters: u8,  // number of events the hero has appeared in
+    pub in_event: u8,    // 1 - the hero is part of the current event
+}
+impl Hero {
+    pub const NEUTRAL_MOOD: u8 = 128;
+    pub const HOSTILE_MOOD: u8 = 64; // heroes below this attack any goblin
+    pub const MOOD_PER_BRIBE_VALUE: u8 = 16;
+    pub const MOOD_PER_REFUSAL: u8 = 48;
+    pub fn init(&mut self, room: u8) {
+        *self = Self {
+            position: room,
+            mood: Self::NEUTRAL_MOOD,
+            ..Default::default()
+        };
+    }
+    pub fn is_in(&self, room: u8) -> bool {
+        self.position == room
+    }
+    /// Wander through one of the room's passages, or stay put.
+    pub fn roam(&mut self, room: &Room, dice: &mut Dice) {
+        if dice.roll(2) == 0 {
+            self.position = room.random_exit(self.position, dice);
+        }
+    }
+    pub fn take_bribe(&mut self, goblin_index: usize, value: u16) {
+        let bit = 1 << goblin_index;
+        self.bribed_by |= bit;
+        self.refused_by &= !bit;
+        let value = value.min(u8::MAX as u16) as u8;
+        self.bribe_value = self.bribe_value.saturating_add(value);
+        self.mood = self
+            .mood
+            .saturating_add(value.saturating_mul(Self::MOOD_PER_BRIBE_VALUE));
+    }
+    pub fn remember_refusal(&mut self, goblin_index: usize) {
+        self.refused_by |= 1 << goblin_index;
+        self.mood = self.mood.saturating_sub(Self::MOOD_PER_REFUSAL);
+    }
+    /// Whether the hero wants revenge on the goblin.
+    pub fn holds_grudge(&self, goblin_index: usize) -> bool {
+        self.refused_by & (1 << goblin_index) != 0 || self.mood < Self::HOSTILE_MOOD
+    }
+    pub fn is_friendly_to(&self, goblin_index: usize) -> bool {
+        self.bribed_by & (1 << goblin_index) != 0 && !self.holds_grudge(goblin_index)
+    }
+    /// Settle the grudge once the hero has attacked the goblin.
+    pub fn settle_grudge(&mut self, goblin_index: usize) {
+        let bit = 1 << goblin_index;
+        self.refused_by &= !bit;
+        self.attacked |= bit;
+        self.mood = self.mood.max(Self::HOSTILE_MOOD);
+    }
+}
@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+/// One slot of a goblin's inventory, holding up to `MAX_STACK` of the same item.
+#[derive(
+    Debug, Copy, Clone, Default, Zeroable, Pod, AnchorSerialize, AnchorDeserialize, PartialEq, Eq,
+)]
+#[repr(C)]
+pub struct ItemStack {
+    pub item: u8,  // item id, 0 - empty slot
+    pub count: u8, // how many of the item are stacked in the slot
+}
+impl ItemStack {
+    pub const MAX_STACK: u8 = 3;
+    pub fn is_empty(&self) -> bool {
+        self.item == 0 || self.count == 0
+    }
+    pub fn can_stack(&self, item: u8) -> bool {
+        self.item == item && self.count < Self::MAX_STACK
+    }
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+/// A [Game](crate::state::Game) as it was laid out before the account grew. Only read
+/// when migrating such a game.
+#[derive(Debug, Copy, Clone, Zeroable, Pod)]
+#[repr(C)]
+pub struct LegacyGame {
+    pub bump: u8,
+    pub creator: Pubkey,
+    pub id: u8,
+    pub game_phase: u8,
+    pub game_rounds: u8,
+    pub num_goblins: u8,
+    pub turn_count: u8,
+    pub turn_goblin: u8,
+    pub turn_phase: u8,
+    pub turn_events: u8,
+    pub rummage_success_min: u8,
+    pub event: u8,
+    pub event_side_effects: [u8; 2],
+    pub event_outcome: u8,
+    pub aftermath_option: u8,
+    pub hero_bribe_rates: [u8; 4],
+    pub available_items: [u8; 4],
+    pub goblins: [LegacyGoblin; 4],
+}
+
+#[derive(Debug, Copy, Clone, Zeroable, Pod)]
+#[repr(C)]
+pub struct LegacyGoblin {
+    pub player: Pubkey,
+    pub health: u8,
+    pub luck: u8,
+    pub greed: u8,
+    pub last_roll: u8,
+    pub last_roll_at: u8,
+    pub held_item: u8,      // held item id
+    pub loot_bag: [u8; 32], // loot values, 0 - empty slot
+}
@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+use crate::state::EventOutcome;
+use crate::utils::Dice;
+
+#[derive(
+    Debug, Copy, Clone, Default, Zeroable, Pod, AnchorSerialize, AnchorDeserialize, PartialEq, Eq,
+)]
+#[repr(C)]
+pub struct Loot {
+    pub rarity: u8,        // 0 - empty slot, 1 (common) to 5 (legendary)
+    pub category: u8,      // LootCategory
+    pub id: u8,            // which piece of its category's set
+    pub found_turn: u8,    // turn_count it was found on
+    pub found_outcome: u8, // 0 - rummaged or dug up, 1+ EventOutcome it was found in
+}
+impl Loot {
+    pub const RARITY_COMMON: u8 = 1;
+    pub const RARITY_UNCOMMON: u8 = 2;
+    pub const RARITY_RARE: u8 = 3;
+    pub const RARITY_EPIC: u8 = 4;
+    pub const RARITY_LEGENDARY: u8 = 5;
+    pub const SET_SIZE: u8 = 4; // distinct pieces in each category's set
+    pub const SET_BONUS: u16 = 10; // extra score for collecting a whole set
+    pub const RARITY_NAMES: [&str; 5] = ["Common", "Uncommon", "Rare", "Epic", "Legendary"];
+    pub const NFT_SYMBOL: &str = "LOOT";
+    pub const FOUND_RUMMAGING: u8 = 0;
+    pub fn random(dice: &mut Dice, found_turn: u8, found_outcome: u8) -> Self {
+        Self {
+            rarity: 1 + dice.roll(Dice::LOOT),
+            category: dice.roll(LootCategory::LEN as u8),
+            id: dice.roll(Self::SET_SIZE),
+            found_turn,
+            found_outcome,
+        }
+    }
+    /// Value of `found_outcome` for loot found in an event with the given outcome.
+    pub fn found_in(outcome: EventOutcome) -> u8 {
+        outcome as u8 + 1
+    }
+    /// Loot from before it was typed, which only had a value.
+    pub fn from_legacy(value: u8) -> Self {
+        Self {
+            rarity: value.min(Self::RARITY_LEGENDARY),
+            category: LootCategory::Coin as u8,
+            id: 0,
+            ..Default::default()
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.rarity == 0
+    }
+    pub fn value(&self) -> u16 {
+        self.rarity as u16
+    }
+    /// Display name such as "Legendary Relic", used for the loot's NFT.
+    pub fn name(&self) -> String {
+        let rarity = Self::RARITY_NAMES
+            .get((self.rarity as usize).wrapping_sub(1))
+            .unwrap_or(&"Unknown");
+        let category = LootCategory::NAMES
+            .get(self.category as usize)
+            .unwrap_or(&"Trinket");
+        format!("{} {}", rarity, category)
+    }
+    /// Inline JSON metadata for the loot's NFT, recording the game, turn, and outcome it
+    /// was found in, and where its goblin placed.
+    pub fn nft_uri(&self, game_id: u64, place: u8) -> String {
+        format!(
+            "data:application/json,{{\"game\":{},\"turn\":{},\"outcome\":{},\"place\":{},\"rarity\":{},\"category\":{},\"piece\":{}}}",
+            game_id, self.found_turn, self.found_outcome, place, self.rarity, self.category, self.id
+        )
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+#[repr(u8)]
+pub enum LootCategory {
+    Gem = 0,
+    Coin,
+    Relic,
+}
+impl LootCategory {
+    pub const LEN: usize = LootCategory::Relic as usize + 1;
+    pub const NAMES: [&str; Self::LEN] = ["Gem", "Coin", "Relic"];
+}
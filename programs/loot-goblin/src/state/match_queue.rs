@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::error::LootGoblinError;
+
+#[account]
+#[derive(Debug, PartialEq, Eq)]
+pub struct MatchQueue {
+    pub bump: u8,
+    pub games_created: u64, // used as the id of the next matched game
+    pub entries: Vec<QueueEntry>,
+}
+impl MatchQueue {
+    pub const MAX_ENTRIES: usize = 16;
+    pub const MAX_RATING_SPREAD: u16 = 200;
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // bump
+        8 + // games_created
+        4 + // entries (len)
+        Self::MAX_ENTRIES * ( // entries (entries)
+            32 + // player
+            2 // rating
+        );
+    pub fn join(&mut self, player: Pubkey, rating: u16) -> Result<()> {
+        if self.entries.iter().any(|entry| entry.player == player) {
+            return err!(LootGoblinError::AlreadyQueued);
+        }
+        if self.entries.len() >= Self::MAX_ENTRIES {
+            return err!(LootGoblinError::QueueFull);
+        }
+        self.entries.push(QueueEntry { player, rating });
+        Ok(())
+    }
+    pub fn leave(&mut self, player: Pubkey) -> Result<()> {
+        let len = self.entries.len();
+        self.entries.retain(|entry| entry.player != player);
+        if self.entries.len() == len {
+            return err!(LootGoblinError::NotQueued);
+        }
+        Ok(())
+    }
+    /// Removes and returns the `num_players` queued players with the closest ratings.
+    pub fn take_closest_match(&mut self, num_players: usize) -> Result<Vec<Pubkey>> {
+        if num_players == 0 || self.entries.len() < num_players {
+            return err!(LootGoblinError::NoMatchFound);
+        }
+        let mut sorted = self.entries.clone();
+        sorted.sort_by_key(|entry| entry.rating);
+        // Find the window of sorted ratings with the smallest spread
+        let (spread, start) = (0..=sorted.len() - num_players)
+            .map(|i| (sorted[i + num_players - 1].rating - sorted[i].rating, i))
+            .min()
+            .unwrap();
+        if spread > Self::MAX_RATING_SPREAD {
+            return err!(LootGoblinError::NoMatchFound);
+        }
+        let players: Vec<Pubkey> = sorted[start..start + num_players]
+            .iter()
+            .map(|entry| entry.player)
+            .collect();
+        self.entries
+            .retain(|entry| !players.contains(&entry.player));
+        Ok(players)
+    }
+}
+
+#[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize, PartialEq, Eq)]
+pub struct QueueEntry {
+    pub player: Pubkey,
+    pub rating: u16,
+}
@@ -1,2 +1,46 @@
+mod achievement;
+mod event_catalog;
+mod faint_rules;
 mod game;
-pub use game::*;
\ No newline at end of file
+mod game_counter;
+mod game_mode;
+mod game_registry;
+mod goblin_class;
+mod hero;
+mod item;
+mod legacy_game;
+mod loot;
+mod match_queue;
+mod player;
+mod room;
+mod season;
+mod session;
+mod side_bet;
+mod status_effect;
+mod tournament;
+mod trade;
+mod turn;
+mod wagered_slap_fight;
+pub use achievement::*;
+pub use event_catalog::*;
+pub use faint_rules::*;
+pub use game::*;
+pub use game_counter::*;
+pub use game_mode::*;
+pub use game_registry::*;
+pub use goblin_class::*;
+pub use hero::*;
+pub use item::*;
+pub use legacy_game::*;
+pub use loot::*;
+pub use match_queue::*;
+pub use player::*;
+pub use room::*;
+pub use season::*;
+pub use session::*;
+pub use side_bet::*;
+pub use status_effect::*;
+pub use tournament::*;
+pub use trade::*;
+pub use turn::*;
+pub use wagered_slap_fight::*;
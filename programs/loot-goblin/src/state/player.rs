@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(Debug, PartialEq, Eq)]
+pub struct Player {
+    pub bump: u8,
+    pub authority: Pubkey,
+    pub rating: u16,
+    pub games_played: u32,
+    pub wins: u32,
+    pub achievements: u32, // bitset of Achievement
+}
+impl Player {
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // bump
+        32 + // authority
+        2 + // rating
+        4 + // games_played
+        4 + // wins
+        4; // achievements
+    pub const INITIAL_RATING: u16 = 1200;
+    pub const RATING_K_FACTOR: i32 = 32;
+    // Expected score (per mille) for rating differences of 0, 50, 100, ... 800
+    const EXPECTED_SCORES: [i32; 17] = [
+        500, 571, 640, 703, 760, 808, 849, 882, 909, 930, 947, 960, 969, 977, 983, 987, 990,
+    ];
+    const EXPECTED_SCORE_STEP: i32 = 50;
+    /// Record achievements earned in a game, returning the ones that are new.
+    pub fn unlock_achievements(&mut self, achievements: u8) -> u32 {
+        let new = achievements as u32 & !self.achievements;
+        self.achievements |= new;
+        new
+    }
+    pub fn init(&mut self, authority: Pubkey, bump: u8) {
+        self.bump = bump;
+        self.authority = authority;
+        self.rating = Self::INITIAL_RATING;
+    }
+    /// Elo expected score (per mille) of a player rated `rating` against `opponent_rating`.
+    pub fn expected_score(rating: u16, opponent_rating: u16) -> i32 {
+        let diff = rating as i32 - opponent_rating as i32;
+        let max_diff = Self::EXPECTED_SCORE_STEP * (Self::EXPECTED_SCORES.len() as i32 - 1);
+        let abs_diff = diff.abs().min(max_diff);
+        // Linearly interpolate between table entries
+        let i = (abs_diff / Self::EXPECTED_SCORE_STEP) as usize;
+        let rem = abs_diff % Self::EXPECTED_SCORE_STEP;
+        let lo = Self::EXPECTED_SCORES[i];
+        let hi = Self::EXPECTED_SCORES[(i + 1).min(Self::EXPECTED_SCORES.len() - 1)];
+        let expected = lo + (hi - lo) * rem / Self::EXPECTED_SCORE_STEP;
+        if diff < 0 {
+            1000 - expected
+        } else {
+            expected
+        }
+    }
+    /// Rating change for a multiplayer game, treating every opponent as a separate match.
+    /// `results` holds `(opponent_rating, actual_score)` pairs where the actual score is
+    /// 1000 for a win, 500 for a draw, and 0 for a loss.
+    pub fn rating_change(rating: u16, results: &[(u16, i32)]) -> i32 {
+        if results.is_empty() {
+            return 0;
+        }
+        let total: i32 = results
+            .iter()
+            .map(|(opponent_rating, actual)| {
+                actual - Self::expected_score(rating, *opponent_rating)
+            })
+            .sum();
+        Self::RATING_K_FACTOR * total / (1000 * results.len() as i32)
+    }
+    pub fn apply_rating_change(&mut self, change: i32) {
+        self.rating = (self.rating as i32 + change).clamp(0, u16::MAX as i32) as u16;
+    }
+}
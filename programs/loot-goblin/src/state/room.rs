@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+use crate::utils::Dice;
+
+/// A room of the dungeon. Rooms are connected by passages recorded as a bitset of
+/// room indices, so clients can draw the map straight from the game account.
+#[derive(
+    Debug, Copy, Clone, Default, Zeroable, Pod, AnchorSerialize, AnchorDeserialize, PartialEq, Eq,
+)]
+#[repr(C)]
+pub struct Room {
+    pub kind: u8,  // RoomKind
+    pub exits: u8, // bitset of connected room indices
+}
+impl Room {
+    pub fn connects_to(&self, room: usize) -> bool {
+        room < 8 && self.exits & (1 << room) != 0
+    }
+    /// A random connected room, or `here` when there's nowhere to go.
+    pub fn random_exit(&self, here: u8, dice: &mut Dice) -> u8 {
+        let exits: Vec<u8> = (0..8).filter(|i| self.connects_to(*i as usize)).collect();
+        if exits.is_empty() {
+            return here;
+        }
+        exits[dice.roll(exits.len() as u8) as usize]
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum RoomKind {
+    Empty = 0,
+    Treasure, // more loot to find
+    Trap,     // more ways to get hurt or lose loot
+    HeroLair, // heroes start here and guard it
+    Shrine,   // healing and luck, and no attacks
+}
+impl RoomKind {
+    pub const LEN: usize = RoomKind::Shrine as usize + 1;
+}
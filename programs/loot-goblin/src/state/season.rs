@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+
+/// A player's running totals on a [Season] leaderboard.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SeasonEntry {
+    pub player: Pubkey, // default - empty slot
+    pub loot: u32,      // summed final scores
+    pub wins: u32,
+    pub games: u32,
+}
+impl SeasonEntry {
+    pub const SIZE: usize = 32 + // player
+        4 + // loot
+        4 + // wins
+        4; // games
+    pub fn is_empty(&self) -> bool {
+        self.player == Pubkey::default()
+    }
+}
+
+/// A stretch of time games are ranked over, paying out a reward pool to the best
+/// players once it ends.
+#[account]
+#[derive(Debug, PartialEq, Eq)]
+pub struct Season {
+    pub bump: u8,
+    pub creator: Pubkey,
+    pub id: u8,
+    pub starts_at: i64, // unix timestamp games must end at or after to count
+    pub ends_at: i64,   // unix timestamp games must end at or before to count
+    pub ranked_by: u8,  // RANKED_BY_*
+    pub closed: u8,     // 0 - no, 1 - rewards paid out
+    pub games_recorded: u32,
+    pub leaderboard: [SeasonEntry; Season::LEADERBOARD_SIZE], // best first, empty slots last
+}
+impl Season {
+    pub const LEADERBOARD_SIZE: usize = 10;
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // bump
+        32 + // creator
+        1 + // id
+        8 + // starts_at
+        8 + // ends_at
+        1 + // ranked_by
+        1 + // closed
+        4 + // games_recorded
+        SeasonEntry::SIZE * Self::LEADERBOARD_SIZE; // leaderboard
+    pub const RANKED_BY_LOOT: u8 = 0;
+    pub const RANKED_BY_WINS: u8 = 1;
+    // Percent of the reward pool paid to 1st, 2nd, and 3rd place
+    pub const REWARD_SHARES: [u64; 3] = [50, 30, 20];
+    pub fn is_during(&self, timestamp: i64) -> bool {
+        self.starts_at <= timestamp && timestamp <= self.ends_at
+    }
+    /// Sort key of an entry, higher is better and empty slots always come last.
+    fn rank(&self, entry: &SeasonEntry) -> (bool, u32, u32) {
+        if self.ranked_by == Self::RANKED_BY_WINS {
+            (!entry.is_empty(), entry.wins, entry.loot)
+        } else {
+            (!entry.is_empty(), entry.loot, entry.wins)
+        }
+    }
+    /// Add a finished game to a player's totals. A player without a spot on the leaderboard
+    /// only gets one if the game alone beats the last entry, since totals are only kept
+    /// for the players on it.
+    pub fn record(&mut self, player: Pubkey, loot: u16, won: bool) {
+        let existing = self
+            .leaderboard
+            .iter()
+            .position(|entry| entry.player == player);
+        let mut entry = existing
+            .map(|i| self.leaderboard[i])
+            .unwrap_or(SeasonEntry {
+                player,
+                ..Default::default()
+            });
+        entry.loot = entry.loot.saturating_add(loot as u32);
+        entry.wins = entry.wins.saturating_add(won as u32);
+        entry.games = entry.games.saturating_add(1);
+        let i = existing.unwrap_or(Self::LEADERBOARD_SIZE - 1);
+        if existing.is_none() && self.rank(&entry) <= self.rank(&self.leaderboard[i]) {
+            return;
+        }
+        self.leaderboard[i] = entry;
+        let mut leaderboard = self.leaderboard;
+        leaderboard.sort_by_key(|entry| std::cmp::Reverse(self.rank(entry)));
+        self.leaderboard = leaderboard;
+    }
+    /// Players that get a share of the reward pool, best first.
+    pub fn rewarded(&self) -> Vec<Pubkey> {
+        self.leaderboard
+            .iter()
+            .take(Self::REWARD_SHARES.len())
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| entry.player)
+            .collect()
+    }
+    /// Reward for each rewarded player, splitting the shares of any missing places among them.
+    pub fn rewards(&self, reward_pool: u64) -> Vec<u64> {
+        let num_rewarded = self.rewarded().len();
+        let shares = &Self::REWARD_SHARES[..num_rewarded];
+        let total_shares: u64 = shares.iter().sum();
+        shares
+            .iter()
+            .map(|share| reward_pool * share / total_shares)
+            .collect()
+    }
+}
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(Debug, PartialEq, Eq)]
+pub struct Session {
+    pub bump: u8,
+    pub game: Pubkey,
+    pub player: Pubkey,         // wallet that delegated control of its goblin
+    pub session_key: Pubkey,    // throwaway key allowed to act for the player
+    pub expires_at: i64,        // unix timestamp
+    pub game_created_slot: u64, // ties the session to this game rather than its address
+}
+impl Session {
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // bump
+        32 + // game
+        32 + // player
+        32 + // session_key
+        8 + // expires_at
+        8; // game_created_slot
+    pub const MAX_DURATION: i64 = 60 * 60 * 24; // 1 day
+    pub fn is_valid_for(&self, signer: Pubkey) -> bool {
+        let clock = Clock::get().expect("couldn't get clock");
+        self.session_key == signer && clock.unix_timestamp < self.expires_at
+    }
+}
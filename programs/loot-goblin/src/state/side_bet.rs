@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Game;
+
+#[account]
+#[derive(Debug, PartialEq, Eq)]
+pub struct SideBetMarket {
+    pub bump: u8,
+    pub game: Pubkey,
+    pub series_game: u8, // game of the series the market was opened on
+    pub mint: Pubkey,    // stake token, default pubkey for SOL
+    pub lock_turn: u8,   // betting locks once the game's turn_count reaches this
+    pub settled: u8,     // 0 - no, 1 - winning goblin decided
+    pub winning_goblin: u8,
+    pub pools: [u64; Game::MAX_PLAYERS], // total stake per goblin index
+    pub num_goblins: u8, // goblins in the game once settled, bets on empty seats are refunded
+    pub cancelled: u8,   // 1 - the game was closed or abandoned, every bet is refunded
+    pub opened_at: i64,  // unix timestamp the market was opened at
+}
+impl SideBetMarket {
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // bump
+        32 + // game
+        1 + // series_game
+        32 + // mint
+        1 + // lock_turn
+        1 + // settled
+        1 + // winning_goblin
+        8 * Game::MAX_PLAYERS + // pools
+        1 + // num_goblins
+        1 + // cancelled
+        8; // opened_at
+    pub const ABANDONED_AFTER: i64 = 30 * 24 * 60 * 60; // games still going after 30 days
+    pub fn is_sol(&self) -> bool {
+        self.mint == Pubkey::default()
+    }
+    pub fn is_open(&self, game: &Game) -> bool {
+        self.settled == 0
+            && game.game_phase <= Game::GAME_PHASE_CRAWL_STARTED
+            && game.turn_count < self.lock_turn
+    }
+    pub fn is_abandoned(&self, now: i64) -> bool {
+        now > self.opened_at + Self::ABANDONED_AFTER
+    }
+    /// Stakes on goblins that took part in the game.
+    pub fn seated_pool(&self) -> u64 {
+        self.pools[..self.num_goblins as usize].iter().sum()
+    }
+    /// Parimutuel payout for a bet. Everyone is refunded if nobody backed the winner, and
+    /// bets on seats that were never filled are always refunded.
+    pub fn payout(&self, bet: &SideBet) -> u64 {
+        if self.cancelled != 0 || bet.goblin_index >= self.num_goblins {
+            return bet.amount;
+        }
+        let winning_pool = self.pools[self.winning_goblin as usize];
+        if winning_pool == 0 {
+            return bet.amount;
+        }
+        if bet.goblin_index != self.winning_goblin {
+            return 0;
+        }
+        (bet.amount as u128 * self.seated_pool() as u128 / winning_pool as u128) as u64
+    }
+}
+
+#[account]
+#[derive(Debug, PartialEq, Eq)]
+pub struct SideBet {
+    pub bump: u8,
+    pub market: Pubkey,
+    pub bettor: Pubkey,
+    pub goblin_index: u8,
+    pub amount: u64,
+}
+impl SideBet {
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // bump
+        32 + // market
+        32 + // bettor
+        1 + // goblin_index
+        8; // amount
+}
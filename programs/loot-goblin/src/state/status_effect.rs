@@ -0,0 +1,16 @@
+/// Timed effects on a goblin. Each goblin keeps a counter of remaining turns per
+/// effect, indexed by the effect.
+#[derive(Debug, Copy, Clone)]
+#[repr(u8)]
+pub enum StatusEffect {
+    Poisoned = 0, // can't heal and takes an extra damage when hurt
+    Cursed,       // takes double damage
+    Shielded,     // takes no damage
+    Blessed,      // better odds of good outcomes
+    GreedyFrenzy, // greed counts double towards finding loot and getting attacked
+    Reflecting,   // attacks bounce onto another goblin
+}
+impl StatusEffect {
+    pub const LEN: usize = StatusEffect::Reflecting as usize + 1;
+    pub const DURATION: u8 = 3; // turns of the affected goblin, see Goblin::apply_status
+}
@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Game;
+
+#[account]
+#[derive(Debug, PartialEq, Eq)]
+pub struct Tournament {
+    pub bump: u8,
+    pub creator: Pubkey,
+    pub id: u8,
+    pub phase: u8,             // TOURNAMENT_PHASE_*
+    pub round: u8,             // current bracket round, starting at 0
+    pub game_rounds: u8,       // game rounds played at each table
+    pub advance_per_table: u8, // top finishers of each table that move on
+    pub games_created: u64,    // used as the id of the next table game
+    pub entry_fee: u64,        // lamports paid into the prize pool when joining
+    pub players: Vec<Pubkey>,  // players still in the running
+    pub tables: Vec<Pubkey>,   // games of the current round
+    pub winners: Vec<Pubkey>,  // final standings, best first
+}
+impl Tournament {
+    pub const MAX_TABLES: usize = 4;
+    pub const MAX_ENTRANTS: usize = Self::MAX_TABLES * Game::MAX_PLAYERS;
+    pub const MAX_ADVANCE_PER_TABLE: u8 = 2;
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // bump
+        32 + // creator
+        1 + // id
+        1 + // phase
+        1 + // round
+        1 + // game_rounds
+        1 + // advance_per_table
+        8 + // games_created
+        8 + // entry_fee
+        4 + // players (len)
+        32 * Self::MAX_ENTRANTS + // players (entries)
+        4 + // tables (len)
+        32 * Self::MAX_TABLES + // tables (entries)
+        4 + // winners (len)
+        32 * Self::PRIZE_SHARES.len(); // winners (entries)
+    pub const TOURNAMENT_PHASE_REGISTRATION: u8 = 0;
+    pub const TOURNAMENT_PHASE_IN_PROGRESS: u8 = 1;
+    pub const TOURNAMENT_PHASE_FINISHED: u8 = 2;
+    pub const TOURNAMENT_PHASE_PAID_OUT: u8 = 3;
+    // Percent of the prize pool paid to 1st, 2nd, and 3rd place
+    pub const PRIZE_SHARES: [u64; 3] = [60, 30, 10];
+    pub fn num_tables(&self) -> usize {
+        self.players.len().div_ceil(Game::MAX_PLAYERS)
+    }
+    /// Players seated at a table this round. Players are dealt round-robin so
+    /// table sizes never differ by more than one.
+    pub fn table_players(&self, table_index: usize) -> Vec<Pubkey> {
+        let num_tables = self.num_tables();
+        self.players
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| i % num_tables == table_index)
+            .map(|(_, player)| *player)
+            .collect()
+    }
+    /// Prize for each winner, splitting the shares of any missing places among them.
+    pub fn prizes(&self, prize_pool: u64) -> Vec<u64> {
+        let shares = &Self::PRIZE_SHARES[..self.winners.len().min(Self::PRIZE_SHARES.len())];
+        let total_shares: u64 = shares.iter().sum();
+        shares
+            .iter()
+            .map(|share| prize_pool * share / total_shares)
+            .collect()
+    }
+}
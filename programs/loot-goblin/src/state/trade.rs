@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+use crate::state::Loot;
+
+/// A goblin's offer to swap loot or items with another goblin. Whatever is
+/// offered is escrowed in the trade until it is accepted or cancelled.
+#[derive(
+    Debug, Copy, Clone, Default, Zeroable, Pod, AnchorSerialize, AnchorDeserialize, PartialEq, Eq,
+)]
+#[repr(C)]
+pub struct Trade {
+    pub is_open: u8,
+    pub with_goblin: u8,          // index of the goblin the offer is made to
+    pub offered_at_turn: u8,      // turn_count when the offer was made
+    pub offered_item: u8,         // escrowed item id, 0 - none
+    pub requested_item: u8,       // item id asked for, 0 - none
+    pub requested_loot_index: u8, // slot of the requested loot in the other goblin's bag
+    pub offered_loot: Loot,       // escrowed loot, empty for none
+    pub requested_loot: Loot,     // loot asked for, empty for none
+}
+impl Trade {
+    pub fn is_open(&self) -> bool {
+        self.is_open != 0
+    }
+}
+
+/// What a goblin puts up and asks for when offering a trade.
+#[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize, PartialEq, Eq)]
+pub struct TradeOffer {
+    pub with_goblin: u8,
+    pub offered_loot_index: Option<u8>, // slot in the offering goblin's bag
+    pub offered_item_slot: Option<u8>,  // inventory slot of an item to offer
+    pub requested_loot_index: Option<u8>, // slot in the other goblin's bag
+    pub requested_item_slot: Option<u8>, // inventory slot of an item to ask for
+}
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+/// How the turn goblin should play out a whole turn in `take_turn`.
+#[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize, PartialEq, Eq)]
+pub struct TurnPlan {
+    pub move_to: Option<u8>,       // room to move to before rummaging
+    pub bribe: Option<TurnBribe>,  // bribe to offer if a hero is met, none - don't bribe
+    pub choices: Vec<u8>,          // event choice per event, the last one repeats once they run out
+    pub risk_it: Option<bool>,     // whether to risk it on every event, none - play like a CPU
+    pub use_item_slot: Option<u8>, // inventory slot to use whenever the item phase comes up
+    pub target: Option<u8>,        // goblin to steal from, a random one if none or not targetable
+    pub stop_after_events: u8,     // 0 - no limit
+    pub stop_below_health: u8,     // 0 - never stop because of health
+}
+impl TurnPlan {
+    pub fn choice(&self, event_index: usize) -> u8 {
+        self.choices
+            .get(event_index)
+            .or(self.choices.last())
+            .copied()
+            .unwrap_or(0)
+    }
+    pub fn should_stop(&self, turn_events: u8, health: u8) -> bool {
+        (self.stop_after_events > 0 && turn_events >= self.stop_after_events)
+            || health < self.stop_below_health
+    }
+}
+
+/// A bribe to offer in `take_turn` when the turn goblin meets a hero.
+#[derive(Debug, Clone, Copy, AnchorSerialize, AnchorDeserialize, PartialEq, Eq)]
+pub struct TurnBribe {
+    pub hero_index: u8,
+    pub loot_index: u8, // slot of the loot to bribe with
+}
+
+/// What happened during a turn played by `take_turn`.
+#[derive(Debug, Clone, Default, AnchorSerialize, AnchorDeserialize, PartialEq, Eq)]
+pub struct TurnSummary {
+    pub goblin: u8,
+    pub score_before: u16,
+    pub score_after: u16,
+    pub health: u8,
+    pub events: Vec<EventSummary>,
+    pub slap_fight: bool,
+    pub turn_ended: bool, // false if the turn is waiting on other goblins, e.g. a wagered slap fight
+}
+
+#[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize, PartialEq, Eq)]
+pub struct EventSummary {
+    pub event: u8,
+    pub choice: u8,
+    pub risk_it: bool,
+    pub outcome: Option<u8>, // EventOutcome, none if the goblin fainted from side-effects
+}
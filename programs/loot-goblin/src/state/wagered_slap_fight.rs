@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::error::LootGoblinError;
+use crate::state::{Game, Goblin, Loot};
+
+/// A slap fight where every goblin secretly commits to a wager of loot or an item, then
+/// reveals it. The winner takes the whole pot.
+#[account]
+#[derive(Debug, PartialEq, Eq)]
+pub struct WageredSlapFight {
+    pub bump: u8,
+    pub game: Pubkey,
+    pub opener: Pubkey,       // paid the rent, refunded once the fight is resolved
+    pub commit_deadline: i64, // unix timestamp wagers must be committed by
+    pub reveal_deadline: i64, // unix timestamp wagers must be revealed by
+    pub committed: u8,        // bitset of goblins that committed a wager
+    pub revealed: u8,         // bitset of goblins that revealed their wager
+    pub commitments: [[u8; 32]; Game::MAX_PLAYERS],
+    pub pot_loot: [Loot; Game::MAX_PLAYERS], // wagered loot per goblin
+    pub pot_items: [u8; Game::MAX_PLAYERS],  // wagered item per goblin
+}
+impl WageredSlapFight {
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // bump
+        32 + // game
+        32 + // opener
+        8 + // commit_deadline
+        8 + // reveal_deadline
+        1 + // committed
+        1 + // revealed
+        32 * Game::MAX_PLAYERS + // commitments
+        5 * Game::MAX_PLAYERS + // pot_loot
+        Game::MAX_PLAYERS; // pot_items
+    pub const COMMIT_WINDOW: i64 = 60;
+    pub const REVEAL_WINDOW: i64 = 60;
+    pub const NO_WAGER: u8 = u8::MAX;
+    /// Hash a goblin commits to, hiding its wager until it's revealed with the same salt.
+    pub fn commitment(
+        goblin_index: u8,
+        loot_index: Option<u8>,
+        item_slot: Option<u8>,
+        salt: &[u8; 32],
+    ) -> [u8; 32] {
+        hashv(&[
+            &[goblin_index],
+            &[loot_index.unwrap_or(Self::NO_WAGER)],
+            &[item_slot.unwrap_or(Self::NO_WAGER)],
+            salt,
+        ])
+        .to_bytes()
+    }
+    pub fn init(&mut self, bump: u8, game_key: Pubkey, game: &Game, opener: Pubkey, now: i64) {
+        self.bump = bump;
+        self.game = game_key;
+        self.opener = opener;
+        self.commit_deadline = now + Self::COMMIT_WINDOW;
+        self.reveal_deadline = self.commit_deadline + Self::REVEAL_WINDOW;
+        // CPU goblins fight without wagering anything, and goblins that are out sit it out
+        for (i, goblin) in game.goblins[..game.num_goblins as usize].iter().enumerate() {
+            if goblin.is_cpu() || game.is_out(i) {
+                self.committed |= 1 << i;
+                self.revealed |= 1 << i;
+            }
+        }
+    }
+    pub fn has_committed(&self, i: usize) -> bool {
+        self.committed & (1 << i) != 0
+    }
+    pub fn has_revealed(&self, i: usize) -> bool {
+        self.revealed & (1 << i) != 0
+    }
+    fn all_committed(&self, num_goblins: u8) -> bool {
+        (0..num_goblins as usize).all(|i| self.has_committed(i))
+    }
+    /// Wagers can be revealed once every goblin has committed or the commit window closes.
+    pub fn is_revealing(&self, num_goblins: u8, now: i64) -> bool {
+        self.all_committed(num_goblins) || now > self.commit_deadline
+    }
+    /// The fight can be resolved once every committed wager is revealed, or the reveal
+    /// window closes. Goblins that didn't reveal in time lose by default.
+    pub fn is_over(&self, num_goblins: u8, now: i64) -> bool {
+        (self.is_revealing(num_goblins, now) && self.revealed == self.committed)
+            || now > self.reveal_deadline
+    }
+    pub fn commit(&mut self, i: usize, commitment: [u8; 32], now: i64) -> Result<()> {
+        if now > self.commit_deadline {
+            return err!(LootGoblinError::WagerDeadlinePassed);
+        }
+        if self.has_committed(i) {
+            return err!(LootGoblinError::WagerAlreadyCommitted);
+        }
+        self.commitments[i] = commitment;
+        self.committed |= 1 << i;
+        Ok(())
+    }
+    /// Check the revealed wager against the goblin's commitment and move it into the pot.
+    pub fn reveal(
+        &mut self,
+        i: usize,
+        goblin: &mut Goblin,
+        loot_index: Option<u8>,
+        item_slot: Option<u8>,
+        salt: &[u8; 32],
+        now: i64,
+    ) -> Result<()> {
+        if now > self.reveal_deadline {
+            return err!(LootGoblinError::WagerDeadlinePassed);
+        }
+        if !self.has_committed(i) || self.has_revealed(i) {
+            return err!(LootGoblinError::WagerNotCommitted);
+        }
+        if Self::commitment(i as u8, loot_index, item_slot, salt) != self.commitments[i] {
+            return err!(LootGoblinError::WagerMismatch);
+        }
+        if let Some(loot_index) = loot_index {
+            let loot = goblin
+                .loot_bag
+                .get_mut(loot_index as usize)
+                .filter(|loot| !loot.is_empty())
+                .ok_or(LootGoblinError::InvalidLootIndex)?;
+            self.pot_loot[i] = std::mem::take(loot);
+        }
+        if let Some(item_slot) = item_slot {
+            if !goblin.has_item_in(item_slot as usize) {
+                return err!(LootGoblinError::InvalidItemSlot);
+            }
+            self.pot_items[i] = goblin.take_item(item_slot as usize);
+        }
+        self.revealed |= 1 << i;
+        Ok(())
+    }
+}
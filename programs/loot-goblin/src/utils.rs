@@ -41,7 +41,6 @@ impl Dice {
     pub const D10: u8 = 10;
     pub const COIN_FLIP: u8 = 1;
     pub const LOOT: u8 = 5;
-    pub const ITEM: u8 = 8;
     pub const ONE_HUNDO: u8 = 100;
     pub const MAX: u8 = 255;
     pub fn new() -> Self {
@@ -49,11 +48,15 @@ impl Dice {
         Self(xorshift64(clock.slot))
     }
     pub fn roll(&mut self, sides: u8) -> u8 {
+        self.roll_u16(sides as u16) as u8
+    }
+    /// Same as [Dice::roll], for when there are more than `u8::MAX` sides.
+    pub fn roll_u16(&mut self, sides: u16) -> u16 {
         let sides = sides as u64;
         let result = self.0 % sides;
         let seed = self.0.saturating_add(result).saturating_add(sides);
         self.0 = xorshift64(seed);
-        result as u8
+        result as u16
     }
     pub fn roll_unique(&mut self, sides: u8, num_rolls: usize) -> HashSet<u8> {
         let mut rolls = HashSet::new();
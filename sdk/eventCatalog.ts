@@ -0,0 +1,30 @@
+// The program's event catalog, compiled in from the same data file by its build script.
+import catalog from "../programs/loot-goblin/data/event_catalog.json";
+
+export interface EventChoice {
+  label: string; // text key of the choice
+  sideEffects: number[]; // ids the choice's side-effect is drawn from
+}
+
+export interface EventDef {
+  id: number;
+  text: string; // text key of the event's narration
+  choices: [EventChoice, EventChoice];
+  weightModifiers: Record<string, number>; // EventOutcome name to weight change
+}
+
+// Side-effect names, indexed by side-effect id
+export const SIDE_EFFECTS: string[] = catalog.side_effects;
+
+export const EVENT_CATALOG: EventDef[] = catalog.events.map((event) => ({
+  id: event.id,
+  text: event.text,
+  choices: event.choices.map((choice) => ({
+    label: choice.label,
+    sideEffects: choice.side_effects.map((name) => SIDE_EFFECTS.indexOf(name)),
+  })) as [EventChoice, EventChoice],
+  weightModifiers: event.weights,
+}));
+
+export const getEvent = (id: number): EventDef | undefined =>
+  EVENT_CATALOG.find((event) => event.id === id);
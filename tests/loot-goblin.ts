@@ -2,6 +2,8 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program, SystemProgram } from "@coral-xyz/anchor";
 import { LootGoblin } from "../target/types/loot_goblin";
 import { expect } from "chai";
+import { createHash } from "crypto";
+import { getEvent } from "../sdk/eventCatalog";
 
 type Game = Awaited<
   ReturnType<Program<LootGoblin>["account"]["game"]["fetch"]>
@@ -19,9 +21,20 @@ const TURN_PHASE_EVENT = 3;
 const TURN_PHASE_OUTCOME = 4;
 const TURN_PHASE_AFTERMATH = 5;
 const TURN_PHASE_SLAP_FIGHT = 6;
+const TURN_PHASE_CHOOSE_TARGET = 7;
+const TURN_PHASE_WAGERED_SLAP_FIGHT = 8;
 const AFTERMATH_OPTION_EITHER = 0;
 const AFTERMATH_OPTION_CONTINUE = 1;
 const AFTERMATH_OPTION_STOP = 2;
+const GAME_MODE_COMPETITIVE = 0;
+const GAME_MODE_COOP = 1;
+const GAME_MODE_TEAMS = 2;
+const DEFAULT_FAINT_RULES = {
+  skipTurn: 0,
+  lootPercent: 0,
+  maxFaints: 0,
+  recoverHealth: 0,
+};
 
 enum EventOutcome {
   GetLoot = 0,
@@ -40,6 +53,37 @@ enum EventOutcome {
   OK,
 }
 
+const SET_SIZE = 4;
+const ITEM_PRICES = [2, 2, 3, 3];
+const MAX_STACK = 3;
+const STATUS_SHIELDED = 2;
+const STATUS_DURATION = 3;
+const SHAMAN = 3;
+const SET_BONUS = 10;
+const SAFE_PLAY_PENALTY_PERCENT = 5;
+const MIN_SCORE_PERCENT = 50;
+
+const itemCount = (goblin: Game["goblins"][number]) =>
+  goblin.inventory.reduce((acc, stack) => acc + stack.count, 0);
+
+// Mirrors `Goblin::score`, including set bonuses and the safe play penalty
+const goblinScore = (goblin: Game["goblins"][number]) => {
+  const loot = goblin.lootBag.filter((loot) => loot.rarity > 0);
+  const value = loot.reduce((acc, loot) => acc + loot.rarity, 0);
+  const pieces = new Map<number, Set<number>>();
+  for (const { category, id } of loot) {
+    pieces.set(category, (pieces.get(category) ?? new Set()).add(id));
+  }
+  const sets = [...pieces.values()].filter((set) => set.size === SET_SIZE);
+  const percent =
+    100 -
+    Math.min(
+      goblin.safePlays * SAFE_PLAY_PENALTY_PERCENT,
+      100 - MIN_SCORE_PERCENT
+    );
+  return Math.floor(((value + sets.length * SET_BONUS) * percent) / 100);
+};
+
 describe("loot-goblin", () => {
   // Configure the client to use the local cluster.
   const provider = anchor.AnchorProvider.env();
@@ -84,7 +128,12 @@ describe("loot-goblin", () => {
     // console.log(ins);
     // console.log([...ins.data]);
     await program.methods
-      .createGame(gameId, gameRounds)
+      .createGame(
+        gameId,
+        gameRounds,
+        GAME_MODE_COMPETITIVE,
+        DEFAULT_FAINT_RULES
+      )
       .accounts({
         game: gamePubkey,
         creator: provider.wallet.publicKey,
@@ -96,12 +145,290 @@ describe("loot-goblin", () => {
 
     // Check if the game has been initialized correctly
     expect(game.id).to.equal(gameId);
+    expect(game.seedScheme).to.equal(0);
     expect(game.gameRounds).to.equal(gameRounds);
+    expect(game.gameMode).to.equal(GAME_MODE_COMPETITIVE);
     expect(game.turnCount).to.equal(0);
     expect(game.gamePhase).to.equal(GAME_PHASE_RECRUIT_GOBLINS);
     prevGame = game;
   });
 
+  // Game counter pubkey
+  const [gameCounterPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("game_counter"), provider.wallet.publicKey.toBuffer()],
+    program.programId
+  );
+
+  // Game registry pubkey
+  const [gameRegistryPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("game_registry")],
+    program.programId
+  );
+
+  it("Creates a game with the next game id", async () => {
+    const gameRounds = 10;
+
+    await program.methods
+      .createGameCounter()
+      .accounts({
+        gameCounter: gameCounterPubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    await program.methods
+      .createGameRegistry()
+      .accounts({
+        gameRegistry: gameRegistryPubkey,
+        payer: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    // New games are seeded with an 8 byte little-endian id
+    const [nextGamePubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("game"),
+        provider.wallet.publicKey.toBuffer(),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+
+    await program.methods
+      .createNextGame(gameRounds, GAME_MODE_COOP, {
+        skipTurn: 1,
+        lootPercent: 25,
+        maxFaints: 3,
+        recoverHealth: 1,
+      })
+      .accounts({
+        game: nextGamePubkey,
+        gameCounter: gameCounterPubkey,
+        gameRegistry: gameRegistryPubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    const game = await program.account.game.fetch(nextGamePubkey);
+    expect(game.wideId.toNumber()).to.equal(0);
+    expect(game.seedScheme).to.equal(1);
+    expect(game.gameMode).to.equal(GAME_MODE_COOP);
+    expect(game.faintRules.maxFaints).to.equal(3);
+    expect(game.gamePhase).to.equal(GAME_PHASE_RECRUIT_GOBLINS);
+    const gameCounter = await program.account.gameCounter.fetch(
+      gameCounterPubkey
+    );
+    expect(gameCounter.nextId.toNumber()).to.equal(1);
+    const gameRegistry = await program.account.gameRegistry.fetch(
+      gameRegistryPubkey
+    );
+    expect(gameRegistry.openGames.map((g) => g.toBase58())).to.include(
+      nextGamePubkey.toBase58()
+    );
+
+    // Closing the game frees its id and removes it from the registry
+    await program.methods
+      .closeGame()
+      .accounts({
+        game: nextGamePubkey,
+        gameRegistry: gameRegistryPubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    expect(await provider.connection.getAccountInfo(nextGamePubkey)).to.be.null;
+  });
+
+  it("Only lets the creator unlist a game that's still recruiting", async () => {
+    const [nextGamePubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("game"),
+        provider.wallet.publicKey.toBuffer(),
+        new anchor.BN(1).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    await program.methods
+      .createNextGame(10, GAME_MODE_COMPETITIVE, DEFAULT_FAINT_RULES)
+      .accounts({
+        game: nextGamePubkey,
+        gameCounter: gameCounterPubkey,
+        gameRegistry: gameRegistryPubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    const game = await program.account.game.fetch(nextGamePubkey);
+    expect(game.createdAt.toNumber()).to.be.greaterThan(0);
+
+    // Nobody else can unlist it until it's been listed for a day
+    const stranger = anchor.web3.Keypair.generate();
+    let error: any = null;
+    try {
+      await program.methods
+        .unlistGame()
+        .accounts({
+          game: nextGamePubkey,
+          gameRegistry: gameRegistryPubkey,
+          signer: stranger.publicKey,
+        })
+        .signers([stranger])
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("WrongGamePhase");
+
+    await program.methods
+      .unlistGame()
+      .accounts({
+        game: nextGamePubkey,
+        gameRegistry: gameRegistryPubkey,
+        signer: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    const gameRegistry = await program.account.gameRegistry.fetch(
+      gameRegistryPubkey
+    );
+    expect(gameRegistry.openGames.map((g) => g.toBase58())).to.not.include(
+      nextGamePubkey.toBase58()
+    );
+
+    await program.methods
+      .closeGame()
+      .accounts({
+        game: nextGamePubkey,
+        gameRegistry: gameRegistryPubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+  });
+
+  it("Migrates a game from the original layout", async () => {
+    // Loaded from tests/fixtures/legacy-game.json, partway through its crawl
+    const legacyGamePubkey = new anchor.web3.PublicKey(
+      "3ACd1fYvr4a3DsUmaS2jEJmtjC7pXmnUkmcXG3WZWrGB"
+    );
+    await program.methods
+      .migrateGame()
+      .accounts({
+        game: legacyGamePubkey,
+        payer: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    const game = await program.account.game.fetch(legacyGamePubkey);
+    expect(game.migrated).to.equal(1);
+    expect(game.seedScheme).to.equal(0);
+    expect(game.id).to.equal(9);
+    expect(game.createdAt.toNumber()).to.equal(0);
+    expect(game.gamePhase).to.equal(GAME_PHASE_CRAWL_STARTED);
+    expect(game.numGoblins).to.equal(2);
+    expect(game.turnCount).to.equal(3);
+    expect(game.heroBribeRates).to.deep.equal([1, 2, 3, 4]);
+    const goblin = game.goblins[0];
+    expect(goblin.player.toBase58()).to.equal(
+      "5B7PKTonAViJGYiABPmtmzJ7SGYUfcqzScAktd7icJ5m"
+    );
+    expect(goblin.greed).to.equal(8);
+    // The held item becomes the first stack and loot values become rarities
+    expect(goblin.inventory[0]).to.deep.equal({ item: 2, count: 1 });
+    expect(
+      goblin.lootBag.slice(0, 3).map((loot) => loot.rarity)
+    ).to.deep.equal([5, 3, 0]);
+    expect(game.goblins[1].lootBag[0].rarity).to.equal(1);
+    // Games that are underway get the dungeon and heroes they'd be missing
+    expect(game.rooms[0].exits).to.not.equal(0);
+    for (const hero of game.heroes) {
+      expect(hero.position).to.be.within(1, game.rooms.length - 1);
+    }
+
+    let error: any = null;
+    try {
+      await program.methods
+        .migrateGame()
+        .accounts({
+          game: legacyGamePubkey,
+          payer: provider.wallet.publicKey,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("GameAlreadyMigrated");
+  });
+
+  it("Only starts team games with a full table", async () => {
+    const teamGameId = 1;
+    const [teamGamePubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("game"),
+        provider.wallet.publicKey.toBuffer(),
+        Buffer.from([teamGameId]),
+      ],
+      program.programId
+    );
+    await program.methods
+      .createGame(teamGameId, 10, GAME_MODE_TEAMS, DEFAULT_FAINT_RULES)
+      .accounts({
+        game: teamGamePubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    let error: any = null;
+    try {
+      await program.methods
+        .recruitGoblins(2, [provider.wallet.publicKey])
+        .accounts({
+          game: teamGamePubkey,
+          creator: provider.wallet.publicKey,
+          gameRegistry: null,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("NotEnoughGoblinsForTeams");
+
+    await program.methods
+      .recruitGoblins(4, [provider.wallet.publicKey])
+      .accounts({
+        game: teamGamePubkey,
+        creator: provider.wallet.publicKey,
+        gameRegistry: null,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    const game = await program.account.game.fetch(teamGamePubkey);
+    expect(game.goblins.map((goblin) => goblin.team)).to.deep.equal([0, 1, 0, 1]);
+  });
+
+  it("Rejects impossible fainting rules", async () => {
+    const badGameId = 2;
+    const [badGamePubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("game"),
+        provider.wallet.publicKey.toBuffer(),
+        Buffer.from([badGameId]),
+      ],
+      program.programId
+    );
+    let error: any = null;
+    try {
+      await program.methods
+        .createGame(badGameId, 10, GAME_MODE_COMPETITIVE, {
+          ...DEFAULT_FAINT_RULES,
+          lootPercent: 101,
+        })
+        .accounts({
+          game: badGamePubkey,
+          creator: provider.wallet.publicKey,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("InvalidFaintRules");
+  });
+
   it("Recruits goblins", async () => {
     // Define the number of goblins and their public keys
     const numGoblins = 4;
@@ -113,6 +440,7 @@ describe("loot-goblin", () => {
       .accounts({
         game: gamePubkey,
         creator: provider.wallet.publicKey,
+        gameRegistry: null,
       })
       .rpc({ skipPreflight: true, commitment: "confirmed" });
 
@@ -132,6 +460,23 @@ describe("loot-goblin", () => {
     prevGame = game;
   });
 
+  it("Chooses a goblin class", async () => {
+    const goblinIndex = 0;
+    const shaman = 3;
+
+    await program.methods
+      .chooseClass(goblinIndex, shaman)
+      .accounts({
+        game: gamePubkey,
+        signer: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    const game = await program.account.game.fetch(gamePubkey);
+    expect(game.goblins[goblinIndex].class).to.equal(shaman);
+    expect(game.goblins[goblinIndex].abilityUsed).to.equal(0);
+  });
+
   it("Finds the greediest goblin", async () => {
     // Call the findGreediestGoblin method
     await program.methods
@@ -154,6 +499,69 @@ describe("loot-goblin", () => {
     prevGame = game;
   });
 
+  it("Starts the crawl without status effects", async () => {
+    const game = await program.account.game.fetch(gamePubkey);
+    for (const goblin of game.goblins.slice(0, game.numGoblins)) {
+      expect(goblin.statusEffects).to.deep.equal([0, 0, 0, 0, 0, 0]);
+    }
+  });
+
+  it("Sends the heroes into the dungeon", async () => {
+    const game = await program.account.game.fetch(gamePubkey);
+    for (let i = 0; i < game.heroes.length; i++) {
+      const hero = game.heroes[i];
+      expect(hero.position).to.be.within(1, game.rooms.length - 1);
+      expect(hero.mood).to.equal(128);
+      expect(hero.bribedBy).to.equal(0);
+      expect(hero.refusedBy).to.equal(0);
+      expect(game.heroBribeRates[i]).to.be.within(1, 5);
+    }
+  });
+
+  it("Leaves out the boss in competitive games", async () => {
+    const game = await program.account.game.fetch(gamePubkey);
+    expect(game.bossHealth).to.equal(0);
+    expect(game.bossMaxHealth).to.equal(0);
+  });
+
+  it("Generates the dungeon map", async () => {
+    const game = await program.account.game.fetch(gamePubkey);
+    expect(game.rooms[0].kind).to.equal(0);
+    expect(game.rooms[0].exits).to.not.equal(0);
+    for (const goblin of game.goblins.slice(0, game.numGoblins)) {
+      expect(goblin.room).to.equal(0);
+    }
+  });
+
+  it("Stocks the shop", async () => {
+    const game = await program.account.game.fetch(gamePubkey);
+    for (const item of game.availableItems) {
+      expect(item).to.be.within(1, ITEM_PRICES.length);
+    }
+  });
+
+  it("Only equips and drops items in the item phase", async () => {
+    const itemSlot = 0;
+    for (const method of [
+      program.methods.equipItem(itemSlot),
+      program.methods.dropItems(itemSlot),
+    ]) {
+      let error: any = null;
+      try {
+        await method
+          .accounts({
+            game: gamePubkey,
+            signer: provider.wallet.publicKey,
+            session: null,
+          })
+          .rpc({ commitment: "confirmed" });
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("WrongTurnPhase");
+    }
+  });
+
   it("Rummages through loot sack", async () => {
     // Call the rummageThroughLootSack method for the current turn's goblin
     await program.methods
@@ -161,6 +569,7 @@ describe("loot-goblin", () => {
       .accounts({
         game: gamePubkey,
         signer: provider.wallet.publicKey, // Assuming the wallet is controlling the current turn's goblin
+        session: null,
       })
       .rpc({ skipPreflight: true, commitment: "confirmed" });
 
@@ -172,7 +581,9 @@ describe("loot-goblin", () => {
     // For example, check if the goblin's loot bag contains new items
     const goblin = game.goblins[game.turnGoblin];
     if (game.rummageSuccessMin > goblin.lastRoll) {
-      expect(goblin.lootBag[0]).to.equal(goblin.lastRoll);
+      expect(goblin.lootBag[0].rarity).to.equal(goblin.lastRoll);
+      expect(goblin.lootBag[0].foundTurn).to.equal(game.turnCount);
+      expect(goblin.lootBag[0].foundOutcome).to.equal(0);
     }
 
     // Check if the turn phase has been updated to the bribe phase
@@ -180,6 +591,23 @@ describe("loot-goblin", () => {
     prevGame = game;
   });
 
+  it("Only uses class abilities before the event", async () => {
+    let error: any = null;
+    try {
+      await program.methods
+        .useClassAbility()
+        .accounts({
+          game: gamePubkey,
+          signer: provider.wallet.publicKey,
+          session: null,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("WrongTurnPhase");
+  });
+
   it("Bribes a hero", async () => {
     // Define the hero index to bribe and the loot index to use for the bribe
     const heroIndex = 0; // Example hero index
@@ -191,6 +619,7 @@ describe("loot-goblin", () => {
       .accounts({
         game: gamePubkey,
         signer: provider.wallet.publicKey, // Assuming the wallet is controlling the current turn's goblin
+        session: null,
       })
       .rpc({ skipPreflight: true, commitment: "confirmed" });
 
@@ -202,16 +631,61 @@ describe("loot-goblin", () => {
     prevGame = game;
   });
 
+  it("Equips and drops items", async () => {
+    let game = await program.account.game.fetch(gamePubkey);
+    let goblin = game.goblins[game.turnGoblin];
+    const itemSlot = goblin.inventory.findIndex((stack) => stack.count > 0);
+    const accounts = {
+      game: gamePubkey,
+      signer: provider.wallet.publicKey,
+      session: null,
+    };
+    // An empty slot can't be equipped
+    if (itemSlot < 0) {
+      let error: any = null;
+      try {
+        await program.methods
+          .equipItem(0)
+          .accounts(accounts)
+          .rpc({ commitment: "confirmed" });
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("InvalidItemSlot");
+      return;
+    }
+
+    await program.methods
+      .equipItem(itemSlot)
+      .accounts(accounts)
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    game = await program.account.game.fetch(gamePubkey);
+    goblin = game.goblins[game.turnGoblin];
+    expect(goblin.equipped).to.equal(itemSlot + 1);
+
+    // Dropping the equipped stack unequips it
+    await program.methods
+      .dropItems(itemSlot)
+      .accounts(accounts)
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    game = await program.account.game.fetch(gamePubkey);
+    goblin = game.goblins[game.turnGoblin];
+    expect(goblin.inventory[itemSlot].count).to.equal(0);
+    expect(goblin.equipped).to.equal(0);
+    expect(game.turnPhase).to.equal(TURN_PHASE_ITEM);
+  });
+
   it("Uses an item", async () => {
-    // Simulate using an item
-    const useItem = false;
+    // Simulate not using an item
+    const itemSlot = null;
 
     // Call the useItem method
     await program.methods
-      .useItem(useItem)
+      .useItem(itemSlot)
       .accounts({
         game: gamePubkey,
         signer: provider.wallet.publicKey, // Assuming the wallet is controlling the current turn's goblin
+        session: null,
       })
       .rpc({ skipPreflight: true, commitment: "confirmed" });
 
@@ -230,6 +704,7 @@ describe("loot-goblin", () => {
       .accounts({
         game: gamePubkey,
         signer: provider.wallet.publicKey, // Assuming the wallet is controlling the current turn's goblin
+        session: null,
       })
       .rpc({ skipPreflight: true, commitment: "confirmed" });
 
@@ -238,6 +713,11 @@ describe("loot-goblin", () => {
 
     // Perform checks to validate the event trigger
     expect(game.event).to.be.greaterThan(0);
+    const event = getEvent(game.event);
+    expect(event).to.not.be.undefined;
+    event.choices.forEach((choice, i) => {
+      expect(choice.sideEffects).to.include(game.eventSideEffects[i]);
+    });
     expect(game.turnEvents).to.equal(1);
     expect(game.turnPhase).to.equal(TURN_PHASE_OUTCOME);
     prevGame = game;
@@ -249,22 +729,27 @@ describe("loot-goblin", () => {
 
     // Call the determineOutcome method
     await program.methods
-      .determineOutcome(choice)
+      .determineOutcome(choice, true)
       .accounts({
         game: gamePubkey,
         signer: provider.wallet.publicKey, // Assuming the wallet is controlling the current turn's goblin
+        session: null,
       })
       .rpc({ skipPreflight: true, commitment: "confirmed" });
 
     // Fetch the updated game account
     const game = await program.account.game.fetch(gamePubkey);
+    expect(game.riskIt).to.equal(1);
     // Perform checks to validate the outcome
     switch (game.eventOutcome) {
       case EventOutcome.GetLoot: {
         const prevGoblin = prevGame.goblins[prevGame.turnGoblin];
         const goblin = game.goblins[game.turnGoblin];
-        const prevLootTotal = prevGoblin.lootBag.reduce((acc, a) => acc + a, 0);
-        const lootTotal = goblin.lootBag.reduce((acc, a) => acc + a, 0);
+        const prevLootTotal = prevGoblin.lootBag.reduce(
+          (acc, a) => acc + a.rarity,
+          0
+        );
+        const lootTotal = goblin.lootBag.reduce((acc, a) => acc + a.rarity, 0);
         expect(lootTotal).to.be.greaterThan(prevLootTotal);
         expect(game.turnPhase).to.equal(TURN_PHASE_AFTERMATH);
         break;
@@ -272,15 +757,19 @@ describe("loot-goblin", () => {
       case EventOutcome.GetItem: {
         const prevGoblin = prevGame.goblins[prevGame.turnGoblin];
         const goblin = game.goblins[game.turnGoblin];
-        expect(prevGoblin.heldItem).to.equal(0);
-        expect(goblin.heldItem).to.be.greaterThan(0);
+        expect(itemCount(goblin)).to.equal(itemCount(prevGoblin) + 1);
         expect(game.turnPhase).to.equal(TURN_PHASE_AFTERMATH);
         break;
       }
       case EventOutcome.StealLoot: {
+        const prevGoblin = prevGame.goblins[prevGame.turnGoblin];
         const goblin = game.goblins[game.turnGoblin];
-        expect(goblin.heldItem).to.equal(0);
-        expect(game.turnPhase).to.equal(TURN_PHASE_AFTERMATH);
+        expect(itemCount(goblin)).to.equal(itemCount(prevGoblin));
+        expect(game.turnPhase).to.equal(TURN_PHASE_CHOOSE_TARGET);
+        break;
+      }
+      case EventOutcome.StealItem: {
+        expect(game.turnPhase).to.equal(TURN_PHASE_CHOOSE_TARGET);
         break;
       }
       case EventOutcome.Heal: {
@@ -307,23 +796,32 @@ describe("loot-goblin", () => {
       case EventOutcome.LoseLoot: {
         const prevGoblin = prevGame.goblins[prevGame.turnGoblin];
         const goblin = game.goblins[game.turnGoblin];
-        const prevLootTotal = prevGoblin.lootBag.reduce((acc, a) => acc + a, 0);
-        const lootTotal = goblin.lootBag.reduce((acc, a) => acc + a, 0);
+        const prevLootTotal = prevGoblin.lootBag.reduce(
+          (acc, a) => acc + a.rarity,
+          0
+        );
+        const lootTotal = goblin.lootBag.reduce((acc, a) => acc + a.rarity, 0);
         expect(lootTotal).to.be.lessThan(prevLootTotal);
         expect(game.turnPhase).to.equal(TURN_PHASE_AFTERMATH);
         break;
       }
       case EventOutcome.LoseItem: {
+        const prevGoblin = prevGame.goblins[prevGame.turnGoblin];
         const goblin = game.goblins[game.turnGoblin];
-        expect(goblin.heldItem).to.equal(0);
+        expect(itemCount(goblin)).to.equal(
+          Math.max(0, itemCount(prevGoblin) - 1)
+        );
         expect(game.turnPhase).to.equal(TURN_PHASE_AFTERMATH);
         break;
       }
       case EventOutcome.LootGotStolen: {
         const prevGoblin = prevGame.goblins[prevGame.turnGoblin];
         const goblin = game.goblins[game.turnGoblin];
-        const prevLootTotal = prevGoblin.lootBag.reduce((acc, a) => acc + a, 0);
-        const lootTotal = goblin.lootBag.reduce((acc, a) => acc + a, 0);
+        const prevLootTotal = prevGoblin.lootBag.reduce(
+          (acc, a) => acc + a.rarity,
+          0
+        );
+        const lootTotal = goblin.lootBag.reduce((acc, a) => acc + a.rarity, 0);
         if (prevLootTotal === 0) {
           expect(lootTotal).to.equal(0);
         } else {
@@ -333,8 +831,11 @@ describe("loot-goblin", () => {
         break;
       }
       case EventOutcome.ItemGotStolen: {
+        const prevGoblin = prevGame.goblins[prevGame.turnGoblin];
         const goblin = game.goblins[game.turnGoblin];
-        expect(goblin.heldItem).to.equal(0);
+        expect(itemCount(goblin)).to.equal(
+          Math.max(0, itemCount(prevGoblin) - 1)
+        );
         expect(game.turnPhase).to.equal(TURN_PHASE_AFTERMATH);
         break;
       }
@@ -357,6 +858,118 @@ describe("loot-goblin", () => {
     prevGame = game;
   });
 
+  it("Chooses whom to steal from", async () => {
+    let game = await program.account.game.fetch(gamePubkey);
+    // Only a won steal needs a target
+    if (game.turnPhase !== TURN_PHASE_CHOOSE_TARGET) {
+      return;
+    }
+
+    // The goblin can't rob itself
+    let error: any = null;
+    try {
+      await program.methods
+        .chooseTarget(game.turnGoblin)
+        .accounts({
+          game: gamePubkey,
+          signer: provider.wallet.publicKey,
+          session: null,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("InvalidTarget");
+
+    const target = (game.turnGoblin + 1) % game.numGoblins;
+    await program.methods
+      .chooseTarget(target)
+      .accounts({
+        game: gamePubkey,
+        signer: provider.wallet.publicKey,
+        session: null,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    game = await program.account.game.fetch(gamePubkey);
+    expect(game.turnPhase).to.equal(TURN_PHASE_AFTERMATH);
+    prevGame = game;
+  });
+
+  it("Settles a wagered slap fight", async () => {
+    let game = await program.account.game.fetch(gamePubkey);
+    const [slapFightPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("wagered_slap_fight"), gamePubkey.toBuffer()],
+      program.programId
+    );
+    const openSlapFight = () =>
+      program.methods
+        .openWageredSlapFight()
+        .accounts({
+          signer: provider.wallet.publicKey,
+          game: gamePubkey,
+          session: null,
+          slapFight: slapFightPubkey,
+        })
+        .rpc({ commitment: "confirmed" });
+
+    // Wagers can only be placed on a pending slap fight
+    if (game.turnPhase !== TURN_PHASE_SLAP_FIGHT) {
+      let error: any = null;
+      try {
+        await openSlapFight();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("WrongTurnPhase");
+      return;
+    }
+
+    await openSlapFight();
+    game = await program.account.game.fetch(gamePubkey);
+    expect(game.turnPhase).to.equal(TURN_PHASE_WAGERED_SLAP_FIGHT);
+
+    // Goblin 0 wagers nothing, the CPU goblins are in without wagering
+    const goblinIndex = 0;
+    const salt = anchor.web3.Keypair.generate().publicKey.toBuffer();
+    const commitment = createHash("sha256")
+      .update(Buffer.from([goblinIndex, 255, 255]))
+      .update(salt)
+      .digest();
+    await program.methods
+      .commitSlapWager(goblinIndex, [...commitment])
+      .accounts({
+        signer: provider.wallet.publicKey,
+        game: gamePubkey,
+        session: null,
+        slapFight: slapFightPubkey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await program.methods
+      .revealSlapWager(goblinIndex, null, null, [...salt])
+      .accounts({
+        signer: provider.wallet.publicKey,
+        game: gamePubkey,
+        session: null,
+        slapFight: slapFightPubkey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await program.methods
+      .resolveWageredSlapFight()
+      .accounts({
+        game: gamePubkey,
+        slapFight: slapFightPubkey,
+        opener: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    game = await program.account.game.fetch(gamePubkey);
+    expect(game.turnGoblin).not.to.equal(prevGame.turnGoblin);
+    expect(await provider.connection.getAccountInfo(slapFightPubkey)).to.be
+      .null;
+    prevGame = game;
+  });
+
   it("Makes a decision in the aftermath of an event", async () => {
     // Simulate making a decision after the event
     const option =
@@ -370,6 +983,7 @@ describe("loot-goblin", () => {
       .accounts({
         game: gamePubkey,
         signer: provider.wallet.publicKey, // Assuming the wallet is controlling the current turn's goblin
+        session: null,
       })
       .rpc({ skipPreflight: true, commitment: "confirmed" });
 
@@ -385,4 +999,1693 @@ describe("loot-goblin", () => {
       expect(game.turnPhase).to.equal(TURN_PHASE_RUMMAGE);
     }
   });
+
+  it("Takes a whole turn with a plan", async () => {
+    let game = await program.account.game.fetch(gamePubkey);
+    // Only a fresh turn can be planned
+    if (game.turnPhase !== TURN_PHASE_RUMMAGE) {
+      return;
+    }
+    const turnCount = game.turnCount;
+
+    await program.methods
+      .takeTurn({
+        choices: Buffer.from([0, 1]),
+        riskIt: null,
+        useItemSlot: null,
+        moveTo: null,
+        bribe: null,
+        target: null,
+        stopAfterEvents: 2,
+        stopBelowHealth: 2,
+      })
+      .accounts({
+        game: gamePubkey,
+        signer: provider.wallet.publicKey,
+        session: null,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    // The turn is over, so it's either the next turn or the end of the crawl
+    game = await program.account.game.fetch(gamePubkey);
+    if (game.gamePhase === GAME_PHASE_CRAWL_STARTED) {
+      expect(game.turnCount).to.equal(turnCount + 1);
+      expect(game.turnPhase).to.equal(TURN_PHASE_RUMMAGE);
+    } else {
+      expect(game.gamePhase).to.equal(GAME_PHASE_CRAWL_ENDED);
+    }
+  });
+
+  it("Buys items from the shop with loot", async () => {
+    let game = await program.account.game.fetch(gamePubkey);
+    // The shop only opens before the goblin heads into an event
+    const shopIndex = game.availableItems.findIndex((item) => item > 0);
+    if (
+      game.gamePhase !== GAME_PHASE_CRAWL_STARTED ||
+      game.turnPhase !== TURN_PHASE_RUMMAGE ||
+      shopIndex < 0
+    ) {
+      return;
+    }
+    const buyItem = (lootIndices: number[]) =>
+      program.methods
+        .buyItem(shopIndex, Buffer.from(lootIndices))
+        .accounts({
+          game: gamePubkey,
+          signer: provider.wallet.publicKey,
+          session: null,
+        })
+        .rpc({ commitment: "confirmed" });
+    const item = game.availableItems[shopIndex];
+    const price = ITEM_PRICES[item - 1];
+    let goblin = game.goblins[game.turnGoblin];
+    const hasRoom = goblin.inventory.some(
+      (stack) =>
+        stack.item === 0 ||
+        stack.count === 0 ||
+        (stack.item === item && stack.count < MAX_STACK)
+    );
+
+    // A full inventory is refused before the loot is counted
+    let error: any = null;
+    try {
+      await buyItem([]);
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal(
+      hasRoom ? "NotEnoughLoot" : "InventoryFull"
+    );
+
+    // Pay with the most valuable loot until the price is covered
+    const lootIndices: number[] = [];
+    let paid = 0;
+    const byValue = goblin.lootBag
+      .map((loot, i) => ({ value: loot.rarity, i }))
+      .filter((loot) => loot.value > 0)
+      .sort((a, b) => b.value - a.value);
+    for (const loot of byValue) {
+      if (paid >= price) {
+        break;
+      }
+      lootIndices.push(loot.i);
+      paid += loot.value;
+    }
+    if (!hasRoom || paid < price) {
+      return;
+    }
+    await buyItem(lootIndices);
+
+    // The loot is spent and the item moves from the shop to the inventory
+    game = await program.account.game.fetch(gamePubkey);
+    goblin = game.goblins[game.turnGoblin];
+    expect(game.availableItems[shopIndex]).to.equal(0);
+    for (const i of lootIndices) {
+      expect(goblin.lootBag[i].rarity).to.equal(0);
+    }
+    expect(
+      goblin.inventory.some((stack) => stack.item === item && stack.count > 0)
+    ).to.be.true;
+
+    // Sold out until the shop restocks
+    error = null;
+    try {
+      await buyItem(lootIndices);
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("ItemNotAvailable");
+  });
+
+  const gamePda = (id: number) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("game"),
+        provider.wallet.publicKey.toBuffer(),
+        Buffer.from([id]),
+      ],
+      program.programId
+    )[0];
+
+  // Another player, funded by the wallet so they can pay for their own accounts
+  const newPlayer = async () => {
+    const player = anchor.web3.Keypair.generate();
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: provider.wallet.publicKey,
+          toPubkey: player.publicKey,
+          lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
+        })
+      )
+    );
+    return player;
+  };
+
+  // SPL token program, driven by hand since the tests don't pull in a token client
+  const tokenProgram = new anchor.web3.PublicKey(
+    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+  );
+
+  // Create an SPL token mint without decimals, minted by the wallet
+  const createMint = async () => {
+    const mint = anchor.web3.Keypair.generate();
+    const space = 82;
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: provider.wallet.publicKey,
+          newAccountPubkey: mint.publicKey,
+          space,
+          lamports:
+            await provider.connection.getMinimumBalanceForRentExemption(space),
+          programId: tokenProgram,
+        }),
+        // InitializeMint2 without a freeze authority
+        new anchor.web3.TransactionInstruction({
+          programId: tokenProgram,
+          keys: [{ pubkey: mint.publicKey, isSigner: false, isWritable: true }],
+          data: Buffer.concat([
+            Buffer.from([20, 0]),
+            provider.wallet.publicKey.toBuffer(),
+            Buffer.from([0]),
+          ]),
+        })
+      ),
+      [mint]
+    );
+    return mint.publicKey;
+  };
+
+  // Create a token account for `owner` holding `amount` freshly minted tokens
+  const createTokenAccount = async (
+    mint: anchor.web3.PublicKey,
+    owner: anchor.web3.PublicKey,
+    amount: number
+  ) => {
+    const account = anchor.web3.Keypair.generate();
+    const space = 165;
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: provider.wallet.publicKey,
+          newAccountPubkey: account.publicKey,
+          space,
+          lamports:
+            await provider.connection.getMinimumBalanceForRentExemption(space),
+          programId: tokenProgram,
+        }),
+        // InitializeAccount3
+        new anchor.web3.TransactionInstruction({
+          programId: tokenProgram,
+          keys: [
+            { pubkey: account.publicKey, isSigner: false, isWritable: true },
+            { pubkey: mint, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.concat([Buffer.from([18]), owner.toBuffer()]),
+        }),
+        // MintTo
+        new anchor.web3.TransactionInstruction({
+          programId: tokenProgram,
+          keys: [
+            { pubkey: mint, isSigner: false, isWritable: true },
+            { pubkey: account.publicKey, isSigner: false, isWritable: true },
+            {
+              pubkey: provider.wallet.publicKey,
+              isSigner: true,
+              isWritable: false,
+            },
+          ],
+          data: Buffer.concat([
+            Buffer.from([7]),
+            new anchor.BN(amount).toArrayLike(Buffer, "le", 8),
+          ]),
+        })
+      ),
+      [account]
+    );
+    return account.publicKey;
+  };
+
+  const tokenBalance = async (account: anchor.web3.PublicKey) =>
+    Number(
+      (await provider.connection.getTokenAccountBalance(account)).value.amount
+    );
+
+  // Start the crawl of a game between the wallet's goblin and a CPU goblin, or a
+  // rival's goblin
+  const startGame = async (
+    id: number,
+    gameRounds: number,
+    {
+      classes = [] as number[],
+      rival = null as anchor.web3.Keypair | null,
+      season = null as anchor.web3.PublicKey | null,
+    } = {}
+  ) => {
+    const pubkey = gamePda(id);
+    await program.methods
+      .createGame(id, gameRounds, GAME_MODE_COMPETITIVE, DEFAULT_FAINT_RULES)
+      .accounts({
+        game: pubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    if (season) {
+      await program.methods
+        .enterSeason()
+        .accounts({
+          season,
+          game: pubkey,
+          creator: provider.wallet.publicKey,
+        })
+        .rpc({ skipPreflight: true, commitment: "confirmed" });
+    }
+    const players = [provider.wallet.publicKey];
+    if (rival) {
+      players.push(rival.publicKey);
+    }
+    await program.methods
+      .recruitGoblins(2, players)
+      .accounts({
+        game: pubkey,
+        creator: provider.wallet.publicKey,
+        gameRegistry: null,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    for (const [goblinIndex, goblinClass] of classes.entries()) {
+      await program.methods
+        .chooseClass(goblinIndex, goblinClass)
+        .accounts({
+          game: pubkey,
+          signer: provider.wallet.publicKey,
+        })
+        .rpc({ skipPreflight: true, commitment: "confirmed" });
+    }
+    await program.methods
+      .findGreediestGoblin()
+      .accounts({
+        game: pubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    return pubkey;
+  };
+
+  // Take the turn goblin's whole turn, stopping after the first event
+  const takeShortTurn = (
+    pubkey: anchor.web3.PublicKey,
+    signer: anchor.web3.Keypair | null = null
+  ) =>
+    program.methods
+      .takeTurn({
+        choices: Buffer.from([0]),
+        riskIt: null,
+        useItemSlot: null,
+        moveTo: null,
+        bribe: null,
+        target: null,
+        stopAfterEvents: 1,
+        stopBelowHealth: 0,
+      })
+      .accounts({
+        game: pubkey,
+        signer: signer ? signer.publicKey : provider.wallet.publicKey,
+        session: null,
+      })
+      .signers(signer ? [signer] : [])
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+  // Take every turn, CPU goblins and rivals included, until the crawl ends
+  const playToEnd = async (
+    pubkey: anchor.web3.PublicKey,
+    rivals: anchor.web3.Keypair[] = []
+  ) => {
+    let game = await program.account.game.fetch(pubkey);
+    while (game.gamePhase === GAME_PHASE_CRAWL_STARTED) {
+      expect(game.turnPhase).to.equal(TURN_PHASE_RUMMAGE);
+      const player = game.goblins[game.turnGoblin].player;
+      const rival = rivals.find((rival) => rival.publicKey.equals(player));
+      await takeShortTurn(pubkey, rival);
+      game = await program.account.game.fetch(pubkey);
+    }
+    return game;
+  };
+
+  // Short game that gets played to the end
+  const finishedGameId = 3;
+  const finishedGamePubkey = gamePda(finishedGameId);
+
+  it("Plays a short game to the end", async () => {
+    await startGame(finishedGameId, 2);
+    const game = await playToEnd(finishedGamePubkey);
+    expect(game.gamePhase).to.equal(GAME_PHASE_CRAWL_ENDED);
+    expect(game.turnCount).to.equal(4);
+    expect(game.endedAt.toNumber()).to.be.greaterThan(0);
+  });
+
+  it("Scores typed loot with set bonuses", async () => {
+    const game = await program.account.game.fetch(finishedGamePubkey);
+    const goblins = game.goblins.slice(0, game.numGoblins);
+    for (const goblin of goblins) {
+      for (const loot of goblin.lootBag.filter((loot) => loot.rarity > 0)) {
+        expect(loot.rarity).to.be.within(1, 5);
+        expect(loot.category).to.be.within(0, 2);
+        expect(loot.id).to.be.within(0, SET_SIZE - 1);
+        // Loot keeps the turn it was found on, even once the game has ended
+        expect(loot.foundTurn).to.be.within(0, game.turnCount);
+      }
+    }
+    // Final scores are carried over to the series once the crawl ends
+    expect(game.seriesScores.slice(0, game.numGoblins)).to.deep.equal(
+      goblins.map(goblinScore)
+    );
+  });
+
+  it("Wears off status effects after the goblin's own turns", async () => {
+    const pubkey = await startGame(4, 4, { classes: [SHAMAN, SHAMAN] });
+    let game = await program.account.game.fetch(pubkey);
+    const goblinIndex = game.turnGoblin;
+
+    // Shamans ward themselves off with a shield
+    await program.methods
+      .useClassAbility()
+      .accounts({
+        game: pubkey,
+        signer: provider.wallet.publicKey,
+        session: null,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    game = await program.account.game.fetch(pubkey);
+    expect(game.goblins[goblinIndex].statusEffects[STATUS_SHIELDED]).to.equal(
+      STATUS_DURATION
+    );
+
+    // The turn it was applied in counts as the first of its turns
+    for (let turns = 1; turns <= STATUS_DURATION; turns++) {
+      do {
+        await takeShortTurn(pubkey);
+        game = await program.account.game.fetch(pubkey);
+      } while (game.turnGoblin !== goblinIndex);
+      expect(
+        game.goblins[goblinIndex].statusEffects[STATUS_SHIELDED]
+      ).to.equal(STATUS_DURATION - turns);
+    }
+  });
+
+  it("Only moves goblins to connected rooms", async () => {
+    const pubkey = await startGame(12, 2);
+    let game = await program.account.game.fetch(pubkey);
+    const here = game.goblins[game.turnGoblin].room;
+    const connects = (room: number, to: number) =>
+      (game.rooms[room].exits & (1 << to)) !== 0;
+    const rooms = game.rooms.map((_, i) => i);
+    const moveGoblin = (room: number) =>
+      program.methods
+        .moveGoblin(room)
+        .accounts({
+          game: pubkey,
+          signer: provider.wallet.publicKey,
+          session: null,
+        })
+        .rpc({ commitment: "confirmed" });
+
+    // There's no passage to a room that isn't connected, not even its own
+    const unconnected = rooms.find((room) => !connects(here, room));
+    let error: any = null;
+    try {
+      await moveGoblin(unconnected);
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("InvalidRoom");
+
+    // Passages lead both ways
+    const connected = rooms.find((room) => connects(here, room));
+    expect(connects(connected, here)).to.be.true;
+    await moveGoblin(connected);
+    const turnCount = game.turnCount;
+    game = await program.account.game.fetch(pubkey);
+    expect(game.goblins[game.turnGoblin].room).to.equal(connected);
+    expect(game.goblins[game.turnGoblin].movedAt).to.equal(turnCount);
+
+    // Goblins only move once a turn
+    error = null;
+    try {
+      await moveGoblin(here);
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("AlreadyMoved");
+  });
+
+  it("Only cancels open trades", async () => {
+    const goblinIndex = 0;
+
+    let error: any = null;
+    try {
+      await program.methods
+        .cancelTrade(goblinIndex)
+        .accounts({
+          game: gamePubkey,
+          signer: provider.wallet.publicKey,
+          session: null,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("NoOpenTrade");
+    const game = await program.account.game.fetch(gamePubkey);
+    for (const trade of game.trades) {
+      expect(trade.isOpen).to.equal(0);
+    }
+  });
+
+  it("Escrows a trade offer and swaps it once accepted", async () => {
+    const rival = await newPlayer();
+    const pubkey = await startGame(11, 2, { rival });
+    const signers = [null, rival];
+    const firstLoot = (goblin: Game["goblins"][number]) =>
+      goblin.lootBag.findIndex((loot) => loot.rarity > 0);
+
+    // Play until a goblin has found something to trade
+    let game = await program.account.game.fetch(pubkey);
+    while (!game.goblins.slice(0, 2).some((goblin) => firstLoot(goblin) >= 0)) {
+      expect(game.gamePhase).to.equal(GAME_PHASE_CRAWL_STARTED);
+      await takeShortTurn(pubkey, signers[game.turnGoblin]);
+      game = await program.account.game.fetch(pubkey);
+    }
+    const from = firstLoot(game.goblins[0]) >= 0 ? 0 : 1;
+    const to = 1 - from;
+    const offeredLootIndex = firstLoot(game.goblins[from]);
+    const offeredLoot = game.goblins[from].lootBag[offeredLootIndex];
+    // Ask for the other goblin's loot in return if it has any
+    const requestedLootIndex = firstLoot(game.goblins[to]);
+    const requestedLoot =
+      requestedLootIndex >= 0
+        ? game.goblins[to].lootBag[requestedLootIndex]
+        : null;
+
+    await program.methods
+      .offerTrade(from, {
+        withGoblin: to,
+        offeredLootIndex,
+        offeredItemSlot: null,
+        requestedLootIndex: requestedLoot ? requestedLootIndex : null,
+        requestedItemSlot: null,
+      })
+      .accounts({
+        game: pubkey,
+        signer: game.goblins[from].player,
+        session: null,
+      })
+      .signers(signers[from] ? [signers[from]] : [])
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    game = await program.account.game.fetch(pubkey);
+    const trade = game.trades[from];
+    expect(trade.isOpen).to.equal(1);
+    expect(trade.withGoblin).to.equal(to);
+    expect(trade.offeredLoot).to.deep.equal(offeredLoot);
+    // The offered loot is held by the trade until it's accepted
+    expect(game.goblins[from].lootBag[offeredLootIndex].rarity).to.equal(0);
+
+    // Only the goblin the trade was offered to can accept it
+    let error: any = null;
+    try {
+      await program.methods
+        .acceptTrade(from)
+        .accounts({
+          game: pubkey,
+          signer: game.goblins[from].player,
+          session: null,
+        })
+        .signers(signers[from] ? [signers[from]] : [])
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("InvalidAuthority");
+
+    await program.methods
+      .acceptTrade(from)
+      .accounts({
+        game: pubkey,
+        signer: game.goblins[to].player,
+        session: null,
+      })
+      .signers(signers[to] ? [signers[to]] : [])
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    game = await program.account.game.fetch(pubkey);
+    expect(game.trades[from].isOpen).to.equal(0);
+    expect(game.goblins[to].lootBag).to.deep.include(offeredLoot);
+    if (requestedLoot) {
+      expect(game.goblins[from].lootBag).to.deep.include(requestedLoot);
+      expect(game.goblins[from].lootBag).to.not.deep.include(offeredLoot);
+    }
+  });
+
+  it("Places a side bet on a goblin", async () => {
+    const lockTurn = 255;
+    const goblinIndex = 0;
+    const amount = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 10);
+    const [marketPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("side_bet_market"),
+        gamePubkey.toBuffer(),
+        Buffer.from([0]),
+      ],
+      program.programId
+    );
+    const [sideBetPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("side_bet"),
+        marketPubkey.toBuffer(),
+        provider.wallet.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+
+    await program.methods
+      .createSideBetMarket(lockTurn)
+      .accounts({
+        game: gamePubkey,
+        market: marketPubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    await program.methods
+      .placeSideBet(goblinIndex, amount)
+      .accounts({
+        game: gamePubkey,
+        market: marketPubkey,
+        sideBet: sideBetPubkey,
+        bettor: provider.wallet.publicKey,
+        bettorTokens: null,
+        vault: null,
+        tokenProgram: null,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    const market = await program.account.sideBetMarket.fetch(marketPubkey);
+    expect(market.lockTurn).to.equal(lockTurn);
+    expect(market.pools[goblinIndex].toString()).to.equal(amount.toString());
+    const sideBet = await program.account.sideBet.fetch(sideBetPubkey);
+    expect(sideBet.goblinIndex).to.equal(goblinIndex);
+    expect(sideBet.amount.toString()).to.equal(amount.toString());
+    // The market keeps the game from being closed until it settles
+    const game = await program.account.game.fetch(gamePubkey);
+    expect(game.sideBetMarkets).to.equal(1);
+    expect(game.openMarkets).to.equal(1);
+
+    // Bets are only cancelled once the game is closed or abandoned
+    let error: any = null;
+    try {
+      await program.methods
+        .cancelSideBetMarket()
+        .accounts({
+          game: gamePubkey,
+          market: marketPubkey,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("GameNotAbandoned");
+  });
+
+  const marketPda = (pubkey: anchor.web3.PublicKey, seriesGame = 0) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("side_bet_market"),
+        pubkey.toBuffer(),
+        Buffer.from([seriesGame]),
+      ],
+      program.programId
+    )[0];
+
+  const sideBetPda = (
+    marketPubkey: anchor.web3.PublicKey,
+    bettor: anchor.web3.PublicKey
+  ) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("side_bet"), marketPubkey.toBuffer(), bettor.toBuffer()],
+      program.programId
+    )[0];
+
+  it("Only lets the creator open a market before the game ends", async () => {
+    const stranger = await newPlayer();
+    let error: any = null;
+    try {
+      await program.methods
+        .createSideBetMarket(255)
+        .accounts({
+          game: finishedGamePubkey,
+          market: marketPda(finishedGamePubkey),
+          creator: stranger.publicKey,
+        })
+        .signers([stranger])
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("InvalidAuthority");
+
+    error = null;
+    try {
+      await program.methods
+        .createSideBetMarket(255)
+        .accounts({
+          game: finishedGamePubkey,
+          market: marketPda(finishedGamePubkey),
+          creator: provider.wallet.publicKey,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("WrongGamePhase");
+  });
+
+  it("Pays winning side bets their share of the pool", async () => {
+    const rival = await newPlayer();
+    const pubkey = await startGame(9, 2, { rival });
+    const marketPubkey = marketPda(pubkey);
+    await program.methods
+      .createSideBetMarket(255)
+      .accounts({
+        game: pubkey,
+        market: marketPubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    // Two bettors back each goblin
+    const wallet = (provider.wallet as anchor.Wallet).payer;
+    const bettors = [wallet, rival, await newPlayer(), await newPlayer()];
+    const stakes = [20_000_000, 10_000_000, 10_000_000, 30_000_000];
+    for (const [i, bettor] of bettors.entries()) {
+      await program.methods
+        .placeSideBet(i % 2, new anchor.BN(stakes[i]))
+        .accounts({
+          game: pubkey,
+          market: marketPubkey,
+          sideBet: sideBetPda(marketPubkey, bettor.publicKey),
+          bettor: bettor.publicKey,
+          bettorTokens: null,
+          vault: null,
+          tokenProgram: null,
+        })
+        .signers([bettor])
+        .rpc({ skipPreflight: true, commitment: "confirmed" });
+    }
+
+    const game = await playToEnd(pubkey, [rival]);
+    await program.methods
+      .settleSideBetMarket()
+      .accounts({
+        game: pubkey,
+        market: marketPubkey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    const market = await program.account.sideBetMarket.fetch(marketPubkey);
+    expect(market.settled).to.equal(1);
+    expect(market.numGoblins).to.equal(2);
+    const scores = game.goblins.slice(0, 2).map(goblinScore);
+    expect(scores[market.winningGoblin]).to.equal(Math.max(...scores));
+    expect((await program.account.game.fetch(pubkey)).openMarkets).to.equal(0);
+
+    // Backers of the winner split the whole pool by stake, the rest get nothing
+    const pool = stakes.reduce((a, b) => a + b, 0);
+    const winningPool = stakes
+      .filter((_, i) => i % 2 === market.winningGoblin)
+      .reduce((a, b) => a + b, 0);
+    let paidOut = 0;
+    for (const [i, bettor] of bettors.entries()) {
+      const sideBetPubkey = sideBetPda(marketPubkey, bettor.publicKey);
+      const payout =
+        i % 2 === market.winningGoblin
+          ? Math.floor((stakes[i] * pool) / winningPool)
+          : 0;
+      const balance = await provider.connection.getBalance(bettor.publicKey);
+      const sideBetRent = await provider.connection.getBalance(sideBetPubkey);
+      await program.methods
+        .claimSideBet()
+        .accounts({
+          market: marketPubkey,
+          sideBet: sideBetPubkey,
+          bettor: bettor.publicKey,
+          bettorTokens: null,
+          vault: null,
+          tokenProgram: null,
+        })
+        .signers([bettor])
+        .rpc({ skipPreflight: true, commitment: "confirmed" });
+      paidOut += payout;
+      expect(await provider.connection.getAccountInfo(sideBetPubkey)).to.be
+        .null;
+      // The wallet pays the transaction fees, so only check the other bettors
+      if (bettor !== wallet) {
+        expect(
+          (await provider.connection.getBalance(bettor.publicKey)) - balance
+        ).to.equal(payout + sideBetRent);
+      }
+    }
+
+    // Only rounding dust is left behind on top of the market's rent
+    const marketInfo = await provider.connection.getAccountInfo(marketPubkey);
+    expect(
+      marketInfo.lamports -
+        (await provider.connection.getMinimumBalanceForRentExemption(
+          marketInfo.data.length
+        ))
+    ).to.equal(pool - paidOut);
+  });
+
+  it("Pays token side bets out of the market's vault", async () => {
+    const rival = await newPlayer();
+    const pubkey = await startGame(10, 2, { rival });
+    const mint = await createMint();
+    const marketPubkey = marketPda(pubkey);
+    const [vaultPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("side_bet_vault"), marketPubkey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .createTokenSideBetMarket(255)
+      .accounts({
+        game: pubkey,
+        market: marketPubkey,
+        mint,
+        vault: vaultPubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    // Each bettor backs a different goblin
+    const wallet = (provider.wallet as anchor.Wallet).payer;
+    const bettors = [wallet, rival];
+    const stakes = [300, 100];
+    const accounts = [];
+    for (const [i, bettor] of bettors.entries()) {
+      accounts.push(await createTokenAccount(mint, bettor.publicKey, 1000));
+      await program.methods
+        .placeSideBet(i, new anchor.BN(stakes[i]))
+        .accounts({
+          game: pubkey,
+          market: marketPubkey,
+          sideBet: sideBetPda(marketPubkey, bettor.publicKey),
+          bettor: bettor.publicKey,
+          bettorTokens: accounts[i],
+          vault: vaultPubkey,
+          tokenProgram,
+        })
+        .signers([bettor])
+        .rpc({ skipPreflight: true, commitment: "confirmed" });
+    }
+    expect(await tokenBalance(vaultPubkey)).to.equal(400);
+
+    await playToEnd(pubkey, [rival]);
+    await program.methods
+      .settleSideBetMarket()
+      .accounts({
+        game: pubkey,
+        market: marketPubkey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    const market = await program.account.sideBetMarket.fetch(marketPubkey);
+
+    // The only backer of the winner takes the whole vault
+    for (const [i, bettor] of bettors.entries()) {
+      await program.methods
+        .claimSideBet()
+        .accounts({
+          market: marketPubkey,
+          sideBet: sideBetPda(marketPubkey, bettor.publicKey),
+          bettor: bettor.publicKey,
+          bettorTokens: accounts[i],
+          vault: vaultPubkey,
+          tokenProgram,
+        })
+        .signers([bettor])
+        .rpc({ skipPreflight: true, commitment: "confirmed" });
+      const won = i === market.winningGoblin ? 400 : 0;
+      expect(await tokenBalance(accounts[i])).to.equal(1000 - stakes[i] + won);
+    }
+    expect(await tokenBalance(vaultPubkey)).to.equal(0);
+  });
+
+  it("Only mints loot NFTs once the game has ended", async () => {
+    const goblinIndex = 0;
+    const lootIndex = 0;
+    const tokenMetadataProgram = new anchor.web3.PublicKey(
+      "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+    );
+    const associatedTokenProgram = new anchor.web3.PublicKey(
+      "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"
+    );
+    const [mintAuthorityPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("loot_mint_authority")],
+      program.programId
+    );
+    const [mintPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("loot_mint"),
+        gamePubkey.toBuffer(),
+        Buffer.from([0]),
+        Buffer.from([goblinIndex]),
+        Buffer.from([lootIndex]),
+      ],
+      program.programId
+    );
+    const [tokenAccountPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        provider.wallet.publicKey.toBuffer(),
+        tokenProgram.toBuffer(),
+        mintPubkey.toBuffer(),
+      ],
+      associatedTokenProgram
+    );
+    const [metadataPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        tokenMetadataProgram.toBuffer(),
+        mintPubkey.toBuffer(),
+      ],
+      tokenMetadataProgram
+    );
+
+    let error: any = null;
+    try {
+      await program.methods
+        .claimLootNft(goblinIndex, lootIndex)
+        .accounts({
+          game: gamePubkey,
+          signer: provider.wallet.publicKey,
+          mintAuthority: mintAuthorityPubkey,
+          mint: mintPubkey,
+          tokenAccount: tokenAccountPubkey,
+          metadata: metadataPubkey,
+          tokenMetadataProgram,
+          associatedTokenProgram,
+          tokenProgram,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("GameNotEnded");
+    expect(await provider.connection.getAccountInfo(mintPubkey)).to.be.null;
+  });
+
+  // Session pubkey of the wallet's session in a game, which is tied to the slot
+  // the game was created in
+  const sessionPda = async (pubkey: anchor.web3.PublicKey) => {
+    const game = await program.account.game.fetch(pubkey);
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("session"),
+        pubkey.toBuffer(),
+        provider.wallet.publicKey.toBuffer(),
+        game.createdSlot.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+  };
+
+  it("Delegates turns to a session key", async () => {
+    const sessionKey = anchor.web3.Keypair.generate();
+    const sessionPubkey = await sessionPda(gamePubkey);
+    const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 60 * 60);
+
+    await program.methods
+      .createSession(sessionKey.publicKey, expiresAt)
+      .accounts({
+        game: gamePubkey,
+        session: sessionPubkey,
+        player: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    const session = await program.account.session.fetch(sessionPubkey);
+    expect(session.sessionKey.toBase58()).to.equal(
+      sessionKey.publicKey.toBase58()
+    );
+    expect(session.player.toBase58()).to.equal(
+      provider.wallet.publicKey.toBase58()
+    );
+    expect(session.expiresAt.toNumber()).to.equal(expiresAt.toNumber());
+
+    await program.methods
+      .revokeSession()
+      .accounts({
+        session: sessionPubkey,
+        player: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    expect(await provider.connection.getAccountInfo(sessionPubkey)).to.be.null;
+  });
+
+  it("Doesn't carry sessions over to a game created at the same address", async () => {
+    const reusedGameId = 8;
+    const pubkey = await startGame(reusedGameId, 2);
+    const sessionKey = anchor.web3.Keypair.generate();
+    const sessionPubkey = await sessionPda(pubkey);
+    await program.methods
+      .createSession(
+        sessionKey.publicKey,
+        new anchor.BN(Math.floor(Date.now() / 1000) + 60 * 60)
+      )
+      .accounts({
+        game: pubkey,
+        session: sessionPubkey,
+        player: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    // Close the game once it's over and start another one under the same id
+    await playToEnd(pubkey);
+    await program.methods
+      .closeGame()
+      .accounts({
+        game: pubkey,
+        gameRegistry: gameRegistryPubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    expect(await startGame(reusedGameId, 2)).to.deep.equal(pubkey);
+    expect(await sessionPda(pubkey)).to.not.deep.equal(sessionPubkey);
+
+    // The old session key no longer controls the wallet's goblin
+    let error: any = null;
+    try {
+      await program.methods
+        .useClassAbility()
+        .accounts({
+          game: pubkey,
+          signer: sessionKey.publicKey,
+          session: sessionPubkey,
+        })
+        .signers([sessionKey])
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("ConstraintSeeds");
+
+    // Its rent can still be reclaimed without the game it was made for
+    await program.methods
+      .revokeSession()
+      .accounts({
+        session: sessionPubkey,
+        player: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    expect(await provider.connection.getAccountInfo(sessionPubkey)).to.be.null;
+  });
+
+  // Player profile pubkey
+  const [playerPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("player"), provider.wallet.publicKey.toBuffer()],
+    program.programId
+  );
+
+  // Match queue pubkey
+  const [matchQueuePubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("match_queue")],
+    program.programId
+  );
+
+  it("Creates a player profile", async () => {
+    await program.methods
+      .createPlayer()
+      .accounts({
+        player: playerPubkey,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    const player = await program.account.player.fetch(playerPubkey);
+    expect(player.authority.toBase58()).to.equal(
+      provider.wallet.publicKey.toBase58()
+    );
+    expect(player.rating).to.equal(1200);
+    expect(player.gamesPlayed).to.equal(0);
+    expect(player.achievements).to.equal(0);
+  });
+
+  it("Only records achievements once the game has ended", async () => {
+    let error: any = null;
+    try {
+      await program.methods
+        .recordAchievements(0)
+        .accounts({
+          game: gamePubkey,
+          player: playerPubkey,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("GameNotEnded");
+  });
+
+  it("Settles player ratings from a finished game", async () => {
+    // Only finished games are rated
+    let error: any = null;
+    try {
+      await program.methods
+        .settleRatings()
+        .accounts({ game: gamePubkey })
+        .remainingAccounts([
+          { pubkey: playerPubkey, isWritable: true, isSigner: false },
+        ])
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("GameNotEnded");
+
+    // Give a rival a profile of their own
+    const rival = await newPlayer();
+    const [rivalPlayerPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("player"), rival.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .createPlayer()
+      .accounts({
+        player: rivalPlayerPubkey,
+        authority: rival.publicKey,
+      })
+      .signers([rival])
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    const pubkey = await startGame(6, 2, { rival });
+    const game = await playToEnd(pubkey, [rival]);
+    const profiles = [
+      { pubkey: playerPubkey, isWritable: true, isSigner: false },
+      { pubkey: rivalPlayerPubkey, isWritable: true, isSigner: false },
+    ];
+    await program.methods
+      .settleRatings()
+      .accounts({ game: pubkey })
+      .remainingAccounts(profiles)
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    // Evenly rated players win or lose half the K-factor of 32, or draw
+    const [score, rivalScore] = game.goblins.slice(0, 2).map(goblinScore);
+    const change = Math.sign(score - rivalScore) * 16;
+    const player = await program.account.player.fetch(playerPubkey);
+    const rivalPlayer = await program.account.player.fetch(rivalPlayerPubkey);
+    expect(player.rating).to.equal(1200 + change);
+    expect(rivalPlayer.rating).to.equal(1200 - change);
+    expect(player.gamesPlayed).to.equal(1);
+    expect(rivalPlayer.gamesPlayed).to.equal(1);
+    expect(player.wins + rivalPlayer.wins).to.equal(1);
+
+    // Ratings are only settled once per game
+    error = null;
+    try {
+      await program.methods
+        .settleRatings()
+        .accounts({ game: pubkey })
+        .remainingAccounts(profiles)
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("RatingsAlreadySettled");
+  });
+
+  it("Doesn't rate a player twice for holding two seats", async () => {
+    const wallet = (provider.wallet as anchor.Wallet).payer;
+    const pubkey = await startGame(7, 2, { rival: wallet });
+    await playToEnd(pubkey, [wallet]);
+
+    let error: any = null;
+    try {
+      await program.methods
+        .settleRatings()
+        .accounts({ game: pubkey })
+        .remainingAccounts([
+          { pubkey: playerPubkey, isWritable: true, isSigner: false },
+          { pubkey: playerPubkey, isWritable: true, isSigner: false },
+        ])
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("DuplicatePlayerProfile");
+    const player = await program.account.player.fetch(playerPubkey);
+    expect(player.gamesPlayed).to.equal(1);
+  });
+
+  it("Joins and leaves the match queue", async () => {
+    await program.methods
+      .createMatchQueue()
+      .accounts({
+        matchQueue: matchQueuePubkey,
+        payer: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    await program.methods
+      .joinMatchQueue()
+      .accounts({
+        matchQueue: matchQueuePubkey,
+        player: playerPubkey,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    let matchQueue = await program.account.matchQueue.fetch(matchQueuePubkey);
+    expect(matchQueue.entries.length).to.equal(1);
+    expect(matchQueue.entries[0].rating).to.equal(1200);
+
+    await program.methods
+      .leaveMatchQueue()
+      .accounts({
+        matchQueue: matchQueuePubkey,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    matchQueue = await program.account.matchQueue.fetch(matchQueuePubkey);
+    expect(matchQueue.entries.length).to.equal(0);
+  });
+
+  // Tournament id
+  const tournamentId = 0;
+
+  // Tournament pubkey
+  const [tournamentPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("tournament"),
+      provider.wallet.publicKey.toBuffer(),
+      Buffer.from([tournamentId]),
+    ],
+    program.programId
+  );
+
+  it("Creates and joins a tournament", async () => {
+    const gameRounds = 3;
+    const advancePerTable = 2;
+    const entryFee = new anchor.BN(1000);
+    const prizePool = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL);
+
+    await program.methods
+      .createTournament(
+        tournamentId,
+        gameRounds,
+        advancePerTable,
+        entryFee,
+        prizePool
+      )
+      .accounts({
+        tournament: tournamentPubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    await program.methods
+      .joinTournament()
+      .accounts({
+        tournament: tournamentPubkey,
+        player: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    const tournament = await program.account.tournament.fetch(
+      tournamentPubkey
+    );
+    expect(tournament.gameRounds).to.equal(gameRounds);
+    expect(tournament.advancePerTable).to.equal(advancePerTable);
+    expect(tournament.players.map((p) => p.toBase58())).to.deep.equal([
+      provider.wallet.publicKey.toBase58(),
+    ]);
+    expect(tournament.phase).to.equal(0);
+  });
+
+  it("Plays a tournament bracket through to the payout", async () => {
+    const bracketId = tournamentId + 1;
+    const [bracketPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("tournament"),
+        provider.wallet.publicKey.toBuffer(),
+        Buffer.from([bracketId]),
+      ],
+      program.programId
+    );
+    const entryFee = 1000;
+    await program.methods
+      .createTournament(
+        bracketId,
+        1,
+        1,
+        new anchor.BN(entryFee),
+        new anchor.BN(anchor.web3.LAMPORTS_PER_SOL)
+      )
+      .accounts({
+        tournament: bracketPubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    // Five players don't fit at one table
+    const rivals: anchor.web3.Keypair[] = [];
+    for (let i = 0; i < 4; i++) {
+      rivals.push(await newPlayer());
+    }
+    const wallet = (provider.wallet as anchor.Wallet).payer;
+    for (const player of [wallet, ...rivals]) {
+      await program.methods
+        .joinTournament()
+        .accounts({
+          tournament: bracketPubkey,
+          player: player.publicKey,
+        })
+        .signers([player])
+        .rpc({ skipPreflight: true, commitment: "confirmed" });
+    }
+    await program.methods
+      .startTournament()
+      .accounts({
+        tournament: bracketPubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    // Play every table of the round, then move the winner of each table on
+    const playRound = async (numTables: number) => {
+      let tournament = await program.account.tournament.fetch(bracketPubkey);
+      const tables: anchor.web3.PublicKey[] = [];
+      for (let i = 0; i < numTables; i++) {
+        const [tablePubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("game"),
+            bracketPubkey.toBuffer(),
+            tournament.gamesCreated.toArrayLike(Buffer, "le", 8),
+          ],
+          program.programId
+        );
+        await program.methods
+          .createTournamentTable()
+          .accounts({
+            tournament: bracketPubkey,
+            game: tablePubkey,
+            creator: provider.wallet.publicKey,
+          })
+          .rpc({ skipPreflight: true, commitment: "confirmed" });
+        tournament = await program.account.tournament.fetch(bracketPubkey);
+        tables.push(tablePubkey);
+      }
+      const games = [];
+      for (const tablePubkey of tables) {
+        games.push(await playToEnd(tablePubkey, [wallet, ...rivals]));
+      }
+      await program.methods
+        .advanceTournament()
+        .accounts({ tournament: bracketPubkey })
+        .remainingAccounts(
+          tables.map((pubkey) => ({
+            pubkey,
+            isWritable: false,
+            isSigner: false,
+          }))
+        )
+        .rpc({ skipPreflight: true, commitment: "confirmed" });
+      return games;
+    };
+
+    // Players are dealt round-robin onto two tables, and each table's winner advances
+    let tournament = await program.account.tournament.fetch(bracketPubkey);
+    expect(tournament.phase).to.equal(1);
+    const tables = await playRound(2);
+    expect(tables.map((game) => game.numGoblins)).to.deep.equal([3, 2]);
+    tournament = await program.account.tournament.fetch(bracketPubkey);
+    expect(tournament.round).to.equal(1);
+    expect(tournament.tables).to.be.empty;
+    expect(tournament.players).to.have.length(2);
+    for (const [i, finalist] of tournament.players.entries()) {
+      const goblins = tables[i].goblins.slice(0, tables[i].numGoblins);
+      const goblin = goblins.find((goblin) => goblin.player.equals(finalist));
+      expect(goblinScore(goblin)).to.equal(
+        Math.max(...goblins.map(goblinScore))
+      );
+    }
+    const finalists = tournament.players.map((p) => p.toBase58());
+
+    // The final table ranks both finalists
+    await playRound(1);
+    tournament = await program.account.tournament.fetch(bracketPubkey);
+    expect(tournament.phase).to.equal(2);
+    expect(tournament.winners.map((p) => p.toBase58()).sort()).to.deep.equal(
+      [...finalists].sort()
+    );
+
+    // 1st and 2nd place split the whole pool 60:30, leaving only rent behind
+    const info = await provider.connection.getAccountInfo(bracketPubkey);
+    const rent = await provider.connection.getMinimumBalanceForRentExemption(
+      info.data.length
+    );
+    const prizePool = info.lamports - rent;
+    expect(prizePool).to.equal(anchor.web3.LAMPORTS_PER_SOL + 5 * entryFee);
+    const prizes = [60, 30].map((share) =>
+      Math.floor((prizePool * share) / 90)
+    );
+    const balances = await Promise.all(
+      tournament.winners.map((winner) => provider.connection.getBalance(winner))
+    );
+    await program.methods
+      .payOutTournament()
+      .accounts({ tournament: bracketPubkey })
+      .remainingAccounts(
+        tournament.winners.map((pubkey) => ({
+          pubkey,
+          isWritable: true,
+          isSigner: false,
+        }))
+      )
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    tournament = await program.account.tournament.fetch(bracketPubkey);
+    expect(tournament.phase).to.equal(3);
+    expect(await provider.connection.getBalance(bracketPubkey)).to.equal(
+      rent + prizePool - prizes[0] - prizes[1]
+    );
+    // The wallet pays the transaction fee, so only check the rivals' prizes
+    for (const [i, winner] of tournament.winners.entries()) {
+      if (!winner.equals(provider.wallet.publicKey)) {
+        const balance = await provider.connection.getBalance(winner);
+        expect(balance - balances[i]).to.equal(prizes[i]);
+      }
+    }
+  });
+
+  // Season id
+  const seasonId = 0;
+
+  // Season pubkey
+  const [seasonPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("season"),
+      provider.wallet.publicKey.toBuffer(),
+      Buffer.from([seasonId]),
+    ],
+    program.programId
+  );
+
+  it("Only counts finished games towards a season", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const rewardPool = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL);
+
+    await program.methods
+      .createSeason(
+        seasonId,
+        new anchor.BN(now - 60),
+        new anchor.BN(now + 3600),
+        0,
+        rewardPool
+      )
+      .accounts({
+        season: seasonPubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    const season = await program.account.season.fetch(seasonPubkey);
+    expect(season.endsAt.toNumber()).to.equal(now + 3600);
+    expect(season.closed).to.equal(0);
+    expect(season.leaderboard.length).to.equal(10);
+
+    let error: any = null;
+    try {
+      await program.methods
+        .recordSeasonGame()
+        .accounts({
+          season: seasonPubkey,
+          game: gamePubkey,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("GameNotEnded");
+
+    error = null;
+    try {
+      await program.methods
+        .closeSeason()
+        .accounts({
+          season: seasonPubkey,
+          creator: provider.wallet.publicKey,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("SeasonNotOver");
+  });
+
+  it("Records entered games in their season and pays out its rewards", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const endsAt = now + 30;
+    const rewardPool = anchor.web3.LAMPORTS_PER_SOL;
+    const rankedSeasonId = 1;
+    const [rankedSeasonPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("season"),
+        provider.wallet.publicKey.toBuffer(),
+        Buffer.from([rankedSeasonId]),
+      ],
+      program.programId
+    );
+    await program.methods
+      .createSeason(
+        rankedSeasonId,
+        new anchor.BN(now - 60),
+        new anchor.BN(endsAt),
+        0,
+        new anchor.BN(rewardPool)
+      )
+      .accounts({
+        season: rankedSeasonPubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    const rival = anchor.web3.Keypair.generate();
+    const pubkey = await startGame(5, 2, {
+      rival,
+      season: rankedSeasonPubkey,
+    });
+    let game = await playToEnd(pubkey, [rival]);
+
+    // Only the season the game was entered into can record it, and only once
+    let error: any = null;
+    try {
+      await program.methods
+        .recordSeasonGame()
+        .accounts({
+          season: seasonPubkey,
+          game: pubkey,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("GameNotInSeason");
+    await program.methods
+      .recordSeasonGame()
+      .accounts({
+        season: rankedSeasonPubkey,
+        game: pubkey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    error = null;
+    try {
+      await program.methods
+        .recordSeasonGame()
+        .accounts({
+          season: rankedSeasonPubkey,
+          game: pubkey,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("GameAlreadyRecorded");
+
+    // Both players are ranked by their final score, best first
+    game = await program.account.game.fetch(pubkey);
+    expect(game.seasonRecordedAt.toNumber()).to.be.greaterThan(0);
+    let season = await program.account.season.fetch(rankedSeasonPubkey);
+    expect(season.gamesRecorded).to.equal(1);
+    const scores = game.goblins.slice(0, 2).map(goblinScore);
+    const entries = season.leaderboard.slice(0, 2);
+    expect(entries.map((entry) => entry.loot)).to.deep.equal(
+      scores.sort((a, b) => b - a)
+    );
+    expect(entries.map((entry) => entry.games)).to.deep.equal([1, 1]);
+    expect(entries[0].wins + entries[1].wins).to.equal(1);
+    expect(
+      entries.map((entry) => entry.player.toBase58()).sort()
+    ).to.deep.equal(
+      [provider.wallet.publicKey, rival.publicKey]
+        .map((player) => player.toBase58())
+        .sort()
+    );
+    expect(season.leaderboard[2].player.toBase58()).to.equal(
+      anchor.web3.PublicKey.default.toBase58()
+    );
+
+    // Wait for the season to end
+    while (Math.floor(Date.now() / 1000) <= endsAt + 2) {
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+    }
+    const rivalRank = entries.findIndex((entry) =>
+      entry.player.equals(rival.publicKey)
+    );
+    const rivalBalance = await provider.connection.getBalance(rival.publicKey);
+    await program.methods
+      .closeSeason()
+      .accounts({
+        season: rankedSeasonPubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .remainingAccounts(
+        entries.map((entry) => ({
+          pubkey: entry.player,
+          isWritable: true,
+          isSigner: false,
+        }))
+      )
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    // 1st and 2nd place split the whole pool 50:30, leaving only rent behind
+    const shares = [50, 30];
+    expect(
+      (await provider.connection.getBalance(rival.publicKey)) - rivalBalance
+    ).to.equal((rewardPool * shares[rivalRank]) / 80);
+    season = await program.account.season.fetch(rankedSeasonPubkey);
+    expect(season.closed).to.equal(1);
+    const seasonInfo = await provider.connection.getAccountInfo(
+      rankedSeasonPubkey
+    );
+    expect(seasonInfo.lamports).to.equal(
+      await provider.connection.getMinimumBalanceForRentExemption(
+        seasonInfo.data.length
+      )
+    );
+  });
+
+  it("Only rematches finished games", async () => {
+    let error: any = null;
+    try {
+      await program.methods
+        .rematch(3)
+        .accounts({
+          game: gamePubkey,
+          creator: provider.wallet.publicKey,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("GameNotEnded");
+
+    const game = await program.account.game.fetch(gamePubkey);
+    expect(game.bestOf).to.equal(0);
+    expect(game.seriesGame).to.equal(0);
+    expect(game.seriesWinner).to.equal(0);
+  });
+
+  it("Rematches a finished game as the next game of a series", async () => {
+    let game = await program.account.game.fetch(finishedGamePubkey);
+    const { firstGoblin, numGoblins } = game;
+    const seriesScores = game.seriesScores.slice(0, numGoblins);
+    const classes = game.goblins.map((goblin) => goblin.class);
+    const players = game.goblins.map((goblin) => goblin.player.toBase58());
+
+    // A series has to be an odd number of games
+    let error: any = null;
+    try {
+      await program.methods
+        .rematch(4)
+        .accounts({
+          game: finishedGamePubkey,
+          creator: provider.wallet.publicKey,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error?.error?.errorCode?.code).to.equal("InvalidSeriesLength");
+
+    await program.methods
+      .rematch(3)
+      .accounts({
+        game: finishedGamePubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    // The game starts over with the same goblins in their seats and classes
+    game = await program.account.game.fetch(finishedGamePubkey);
+    expect(game.gamePhase).to.equal(GAME_PHASE_FIND_GREEDIEST);
+    expect(game.turnCount).to.equal(0);
+    expect(game.endedAt.toNumber()).to.equal(0);
+    expect(game.bestOf).to.equal(3);
+    expect(game.seriesGame).to.equal(1);
+    expect(game.seriesWinner).to.equal(0);
+    expect(game.numGoblins).to.equal(numGoblins);
+    expect(game.goblins.map((goblin) => goblin.class)).to.deep.equal(classes);
+    expect(
+      game.goblins.map((goblin) => goblin.player.toBase58())
+    ).to.deep.equal(players);
+    for (const goblin of game.goblins.slice(0, numGoblins)) {
+      expect(goblin.health).to.be.above(0);
+      expect(goblin.lootBag.every((loot) => loot.rarity === 0)).to.equal(true);
+    }
+    expect(game.seriesScores.slice(0, numGoblins)).to.deep.equal(seriesScores);
+
+    // Each game of the series gets its own side bet market
+    const marketPubkey = marketPda(finishedGamePubkey, game.seriesGame);
+    await program.methods
+      .createSideBetMarket(255)
+      .accounts({
+        game: finishedGamePubkey,
+        market: marketPubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    const market = await program.account.sideBetMarket.fetch(marketPubkey);
+    expect(market.seriesGame).to.equal(1);
+
+    // The first turn passes to the next goblin over
+    await program.methods
+      .findGreediestGoblin()
+      .accounts({
+        game: finishedGamePubkey,
+        creator: provider.wallet.publicKey,
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    game = await program.account.game.fetch(finishedGamePubkey);
+    expect(game.firstGoblin).to.equal((firstGoblin + 1) % numGoblins);
+    expect(game.turnGoblin).to.equal(game.firstGoblin);
+
+    // Final scores and wins add up over the series
+    game = await playToEnd(finishedGamePubkey);
+    const goblins = game.goblins.slice(0, numGoblins);
+    expect(game.seriesScores.slice(0, numGoblins)).to.deep.equal(
+      goblins.map((goblin, i) => seriesScores[i] + goblinScore(goblin))
+    );
+    expect(
+      game.seriesWins.slice(0, numGoblins).reduce((a, b) => a + b, 0)
+    ).to.equal(2);
+    // Winning both games takes a best-of-3 series
+    const sweeper = game.seriesWins.findIndex((wins) => wins === 2);
+    expect(game.seriesWinner).to.equal(sweeper + 1);
+  });
 });